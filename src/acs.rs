@@ -0,0 +1,335 @@
+//            DO WHAT THE FUCK YOU WANT TO PUBLIC LICENSE
+//                    Version 2, December 2004
+//
+// Copyleft (ↄ) meh. <meh@schizofreni.co> | http://meh.schizofreni.co
+//
+// Everyone is permitted to copy and distribute verbatim or modified
+// copies of this license document, and changing it is allowed as long
+// as the name is changed.
+//
+//            DO WHAT THE FUCK YOU WANT TO PUBLIC LICENSE
+//   TERMS AND CONDITIONS FOR COPYING, DISTRIBUTION AND MODIFICATION
+//
+//  0. You just DO WHAT THE FUCK YOU WANT TO.
+
+//! Alternate character set for line drawing.
+//!
+//! ## Example
+//!
+//! ```
+//! use terminfo::Database;
+//! use terminfo::acs::{Acs, Glyph, Rendering};
+//!
+//! let info = Database::from_path("tests/xterm-256color").unwrap();
+//! let acs  = Acs::new(&info);
+//!
+//! assert_eq!(Rendering::Alternate(b'l'), acs.render(Glyph::UlCorner));
+//! assert_eq!(Rendering::Unicode('┌'), acs.clone().utf8(true).render(Glyph::UlCorner));
+//!
+//! let mut output = Vec::new();
+//! acs.write(&mut output, Glyph::HLine).unwrap();
+//! assert_eq!(b"\x1B(0q\x1B(B".to_vec(), output);
+//! ```
+
+use std::collections::BTreeMap;
+use std::io::{self, Write};
+
+use crate::capability as cap;
+use crate::Database;
+
+/// A line drawing glyph, named after the ncurses `ACS_*` constants.
+#[derive(Eq, PartialEq, Copy, Clone, Hash, Debug)]
+pub enum Glyph {
+	/// `ACS_ULCORNER`, upper left corner.
+	UlCorner,
+
+	/// `ACS_LLCORNER`, lower left corner.
+	LlCorner,
+
+	/// `ACS_URCORNER`, upper right corner.
+	UrCorner,
+
+	/// `ACS_LRCORNER`, lower right corner.
+	LrCorner,
+
+	/// `ACS_LTEE`, tee pointing right.
+	LTee,
+
+	/// `ACS_RTEE`, tee pointing left.
+	RTee,
+
+	/// `ACS_BTEE`, tee pointing up.
+	BTee,
+
+	/// `ACS_TTEE`, tee pointing down.
+	TTee,
+
+	/// `ACS_HLINE`, horizontal line.
+	HLine,
+
+	/// `ACS_VLINE`, vertical line.
+	VLine,
+
+	/// `ACS_PLUS`, large plus or crossover.
+	Plus,
+
+	/// `ACS_S1`, scan line 1.
+	S1,
+
+	/// `ACS_S3`, scan line 3.
+	S3,
+
+	/// `ACS_S7`, scan line 7.
+	S7,
+
+	/// `ACS_S9`, scan line 9.
+	S9,
+
+	/// `ACS_DIAMOND`, diamond.
+	Diamond,
+
+	/// `ACS_CKBOARD`, checker board.
+	CkBoard,
+
+	/// `ACS_DEGREE`, degree symbol.
+	Degree,
+
+	/// `ACS_PLMINUS`, plus/minus.
+	PlMinus,
+
+	/// `ACS_BULLET`, bullet.
+	Bullet,
+
+	/// `ACS_LARROW`, arrow pointing left.
+	LArrow,
+
+	/// `ACS_RARROW`, arrow pointing right.
+	RArrow,
+
+	/// `ACS_DARROW`, arrow pointing down.
+	DArrow,
+
+	/// `ACS_UARROW`, arrow pointing up.
+	UArrow,
+
+	/// `ACS_BOARD`, board of squares.
+	Board,
+
+	/// `ACS_LANTERN`, lantern symbol.
+	Lantern,
+
+	/// `ACS_BLOCK`, solid square block.
+	Block,
+
+	/// `ACS_LEQUAL`, less than or equal.
+	LEqual,
+
+	/// `ACS_GEQUAL`, greater than or equal.
+	GEqual,
+
+	/// `ACS_PI`, greek pi.
+	Pi,
+
+	/// `ACS_NEQUAL`, not equal.
+	NEqual,
+
+	/// `ACS_STERLING`, pound sterling.
+	Sterling,
+}
+
+/// The VT100 character, the Unicode equivalent and the ASCII fallback of every
+/// glyph, as used by ncurses.
+const GLYPHS: &[(Glyph, u8, char, u8)] = &[
+	(Glyph::UlCorner, b'l', '┌', b'+'),
+	(Glyph::LlCorner, b'm', '└', b'+'),
+	(Glyph::UrCorner, b'k', '┐', b'+'),
+	(Glyph::LrCorner, b'j', '┘', b'+'),
+	(Glyph::LTee, b't', '├', b'+'),
+	(Glyph::RTee, b'u', '┤', b'+'),
+	(Glyph::BTee, b'v', '┴', b'+'),
+	(Glyph::TTee, b'w', '┬', b'+'),
+	(Glyph::HLine, b'q', '─', b'-'),
+	(Glyph::VLine, b'x', '│', b'|'),
+	(Glyph::Plus, b'n', '┼', b'+'),
+	(Glyph::S1, b'o', '⎺', b'-'),
+	(Glyph::S3, b'p', '⎻', b'-'),
+	(Glyph::S7, b'r', '⎼', b'-'),
+	(Glyph::S9, b's', '⎽', b'_'),
+	(Glyph::Diamond, b'`', '◆', b'+'),
+	(Glyph::CkBoard, b'a', '▒', b':'),
+	(Glyph::Degree, b'f', '°', b'\''),
+	(Glyph::PlMinus, b'g', '±', b'#'),
+	(Glyph::Bullet, b'~', '·', b'o'),
+	(Glyph::LArrow, b',', '←', b'<'),
+	(Glyph::RArrow, b'+', '→', b'>'),
+	(Glyph::DArrow, b'.', '↓', b'v'),
+	(Glyph::UArrow, b'-', '↑', b'^'),
+	(Glyph::Board, b'h', '▒', b'#'),
+	(Glyph::Lantern, b'i', '☃', b'#'),
+	(Glyph::Block, b'0', '▮', b'#'),
+	(Glyph::LEqual, b'y', '≤', b'<'),
+	(Glyph::GEqual, b'z', '≥', b'>'),
+	(Glyph::Pi, b'{', 'π', b'*'),
+	(Glyph::NEqual, b'|', '≠', b'!'),
+	(Glyph::Sterling, b'}', '£', b'f'),
+];
+
+impl Glyph {
+	/// All the glyphs.
+	pub fn all() -> impl Iterator<Item = Glyph> {
+		GLYPHS.iter().map(|&(glyph, ..)| glyph)
+	}
+
+	/// The glyph for the given VT100 character.
+	pub fn from_vt100(code: u8) -> Option<Self> {
+		GLYPHS.iter().find(|&&(_, other, ..)| other == code).map(|&(glyph, ..)| glyph)
+	}
+
+	/// The VT100 character used in `acs_chars`.
+	pub fn vt100(self) -> u8 {
+		self.entry().1
+	}
+
+	/// The Unicode equivalent.
+	pub fn unicode(self) -> char {
+		self.entry().2
+	}
+
+	/// The ASCII fallback.
+	pub fn ascii(self) -> u8 {
+		self.entry().3
+	}
+
+	fn entry(self) -> &'static (Glyph, u8, char, u8) {
+		GLYPHS.iter().find(|&&(glyph, ..)| glyph == self).unwrap()
+	}
+}
+
+/// How to emit a glyph.
+#[derive(Eq, PartialEq, Copy, Clone, Debug)]
+pub enum Rendering {
+	/// The byte in the alternate character set, between `enter_alt_charset_mode`
+	/// and `exit_alt_charset_mode`.
+	Alternate(u8),
+
+	/// The Unicode character.
+	Unicode(char),
+
+	/// The ASCII fallback.
+	Ascii(u8),
+}
+
+/// The alternate character set of a terminal.
+///
+/// Like ncurses, glyphs are emitted as Unicode when the terminal is UTF-8
+/// capable, through the alternate character set when the terminal maps them,
+/// and as an ASCII approximation otherwise.
+#[derive(Eq, PartialEq, Clone, Default, Debug)]
+pub struct Acs {
+	map: BTreeMap<u8, u8>,
+	enter: Option<Vec<u8>>,
+	exit: Option<Vec<u8>>,
+	enable: Option<Vec<u8>>,
+	utf8: bool,
+}
+
+impl Acs {
+	/// Load the alternate character set of the given terminal.
+	pub fn new(info: &Database) -> Self {
+		fn string<T: AsRef<[u8]>>(value: Option<T>) -> Option<Vec<u8>> {
+			value.map(|value| value.as_ref().to_vec()).filter(|value| !value.is_empty())
+		}
+
+		Acs {
+			map: info.get::<cap::AcsChars>().map(|pairs| parse(pairs.as_ref())).unwrap_or_default(),
+			enter: string(info.get::<cap::EnterAltCharsetMode>()),
+			exit: string(info.get::<cap::ExitAltCharsetMode>()),
+			enable: string(info.get::<cap::EnaAcs>()),
+			utf8: false,
+		}
+	}
+
+	/// Whether the terminal is UTF-8 capable, in which case glyphs are emitted
+	/// as Unicode.
+	pub fn utf8(mut self, value: bool) -> Self {
+		self.utf8 = value;
+		self
+	}
+
+	/// The sequence to send once before using the alternate character set, if
+	/// any.
+	pub fn enable(&self) -> Option<&[u8]> {
+		self.enable.as_deref()
+	}
+
+	/// The byte the terminal displays as the glyph in the alternate character
+	/// set.
+	pub fn get(&self, glyph: Glyph) -> Option<u8> {
+		self.map.get(&glyph.vt100()).copied()
+	}
+
+	/// Find how to emit the glyph.
+	pub fn render(&self, glyph: Glyph) -> Rendering {
+		if self.utf8 {
+			return Rendering::Unicode(glyph.unicode());
+		}
+
+		match self.get(glyph) {
+			Some(byte) if self.enter.is_some() => Rendering::Alternate(byte),
+
+			_ => Rendering::Ascii(glyph.ascii()),
+		}
+	}
+
+	/// Write the glyph to the given output.
+	pub fn write<W: Write>(&self, mut output: W, glyph: Glyph) -> io::Result<()> {
+		match self.render(glyph) {
+			Rendering::Alternate(byte) => {
+				output.write_all(self.enter.as_deref().unwrap_or_default())?;
+				output.write_all(&[byte])?;
+				output.write_all(self.exit.as_deref().unwrap_or_default())
+			}
+
+			Rendering::Unicode(ch) => write!(output, "{}", ch),
+
+			Rendering::Ascii(byte) => output.write_all(&[byte]),
+		}
+	}
+}
+
+/// Parse the pairs of VT100 characters and terminal bytes in `acs_chars`.
+pub fn parse(pairs: &[u8]) -> BTreeMap<u8, u8> {
+	pairs.chunks_exact(2).map(|pair| (pair[0], pair[1])).collect()
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+
+	#[test]
+	fn pairs() {
+		let map = parse(b"``aaffggjjkkllmmnnooppqqrrssttuuvvwwxxyyzz{{||}}~~x");
+		assert_eq!(Some(&b'q'), map.get(&b'q'));
+		assert_eq!(None, map.get(&b'0'));
+
+		for glyph in Glyph::all() {
+			assert_eq!(Some(glyph), Glyph::from_vt100(glyph.vt100()));
+		}
+	}
+
+	#[test]
+	fn render() {
+		let mut info = Database::new();
+		info.name("test").raw("acsc", "qxjm").raw("smacs", "\x0E").raw("rmacs", "\x0F");
+		let acs = Acs::new(&info.build().unwrap());
+
+		assert_eq!(Rendering::Alternate(b'x'), acs.render(Glyph::HLine));
+		assert_eq!(Rendering::Ascii(b'|'), acs.render(Glyph::VLine));
+		assert_eq!(Rendering::Unicode('│'), acs.clone().utf8(true).render(Glyph::VLine));
+
+		let mut output = Vec::new();
+		acs.write(&mut output, Glyph::LrCorner).unwrap();
+		acs.write(&mut output, Glyph::UArrow).unwrap();
+		assert_eq!(b"\x0Em\x0F^".to_vec(), output);
+	}
+}
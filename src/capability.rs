@@ -15,10 +15,11 @@
 //! Standard capabilities.
 
 use std::borrow::Cow;
-use std::io::Write;
+use std::fmt;
+use std::io::{BufWriter, Write};
 
 use crate::error;
-use crate::expand::{Context, Expand, Parameter};
+use crate::expand::{self, Context, Observer, Parameter, Scratch};
 
 /// A trait for any object that will represent a terminal capability.
 pub trait Capability<'a>: Sized {
@@ -46,33 +47,168 @@ pub enum Value {
 }
 
 /// Expansion helper struct.
+///
+/// Referencing a parameter that was not provided fails the expansion, the
+/// parameters of typed capabilities are always provided and default to `0`.
 #[derive(Debug)]
 pub struct Expansion<'a, T: 'a + AsRef<[u8]>> {
 	string: &'a T,
+	name: &'static str,
 	params: [Parameter; 9],
+	count: usize,
+	overflow: Vec<Parameter>,
 	context: Option<&'a mut Context>,
+	scratch: Option<&'a mut Scratch>,
+	observer: Option<&'a mut dyn Observer>,
 }
 
 impl<'a, T: AsRef<[u8]>> Expansion<'a, T> {
+	/// Create an expansion with the given amount of default parameters.
+	///
+	/// Used by the `capability!` macro, not part of the public API.
+	#[doc(hidden)]
+	pub fn new(string: &'a T, name: &'static str, count: usize) -> Self {
+		let mut expansion = Expansion {
+			string,
+			name,
+			params: Default::default(),
+			count: 0,
+			overflow: Vec::new(),
+			context: None,
+			scratch: None,
+			observer: None,
+		};
+
+		if count > 0 {
+			expansion = expansion.set(count - 1, Parameter::default());
+		}
+
+		expansion
+	}
+
+	/// Push a parameter after the ones already provided.
+	pub fn push<P: Into<Parameter>>(self, value: P) -> Self {
+		let index = self.count;
+		self.set(index, value)
+	}
+
+	/// Set the parameter at the given index, starting from `0` for `%p1`.
+	///
+	/// Any parameter before it that was not provided is set to `0`.
+	pub fn set<P: Into<Parameter>>(mut self, index: usize, value: P) -> Self {
+		if index >= self.params.len() {
+			let index = index - self.params.len();
+
+			if index >= self.overflow.len() {
+				self.overflow.resize(index + 1, Default::default());
+			}
+
+			self.overflow[index] = value.into();
+		} else {
+			self.params[index] = value.into();
+		}
+
+		self.count = self.count.max(index + 1);
+		self
+	}
+
+	/// Push all the given parameters after the ones already provided.
+	pub fn extend<I>(mut self, iter: I) -> Self
+	where
+		I: IntoIterator,
+		I::Item: Into<Parameter>,
+	{
+		for value in iter {
+			self = self.push(value);
+		}
+
+		self
+	}
+
 	/// Expand using the given context.
 	pub fn with<'c: 'a>(mut self, context: &'c mut Context) -> Self {
 		self.context = Some(context);
 		self
 	}
 
+	/// Expand using the given scratch space.
+	///
+	/// When a scratch space is given the output is not buffered.
+	pub fn scratch<'c: 'a>(mut self, scratch: &'c mut Scratch) -> Self {
+		self.scratch = Some(scratch);
+		self
+	}
+
+	/// Report every executed item to the given observer.
+	pub fn trace<'c: 'a>(mut self, observer: &'c mut dyn Observer) -> Self {
+		self.observer = Some(observer);
+		self
+	}
+
 	/// Expand to the given output.
 	pub fn to<W: Write>(self, output: W) -> error::Result<()> {
-		self.string.as_ref().expand(
+		if self.scratch.is_some() {
+			return self.expand(output);
+		}
+
+		let mut output = BufWriter::new(output);
+		self.expand(&mut output)?;
+		output.flush()?;
+
+		Ok(())
+	}
+
+	/// Expand to the given formatter.
+	pub fn to_fmt<W: fmt::Write + ?Sized>(mut self, output: &mut W) -> error::Result<()> {
+		let mut default = Scratch::default();
+		let scratch = self.scratch.take().unwrap_or(&mut default);
+
+		expand::format(output, scratch, |buffer, scratch| self.evaluate(buffer, scratch))
+	}
+
+	/// Expand appending to the given vector.
+	pub fn append(self, output: &mut Vec<u8>) -> error::Result<()> {
+		self.expand(output)
+	}
+
+	fn expand<W: Write>(mut self, output: W) -> error::Result<()> {
+		let mut default = Scratch::default();
+		let scratch = self.scratch.take().unwrap_or(&mut default);
+
+		self.evaluate(output, scratch)
+	}
+
+	fn evaluate<W: Write>(self, output: W, scratch: &mut Scratch) -> error::Result<()> {
+		let mut params = Cow::Borrowed(&self.params[..self.count.min(self.params.len())]);
+
+		if !self.overflow.is_empty() {
+			params.to_mut().extend(self.overflow);
+		}
+
+		let name = self.name;
+
+		expand::evaluate(
+			self.string.as_ref(),
 			output,
-			&self.params,
+			&params,
+			0,
 			self.context.unwrap_or(&mut Default::default()),
+			scratch,
+			self.observer,
 		)
+		.map_err(|mut err| {
+			if let error::Error::Expand(ref mut details) = err {
+				details.capability = Some(name);
+			}
+
+			err
+		})
 	}
 
 	/// Expand into a vector.
 	pub fn to_vec(self) -> error::Result<Vec<u8>> {
 		let mut result = Vec::with_capacity(self.string.as_ref().len());
-		self.to(&mut result)?;
+		self.append(&mut result)?;
 		Ok(result)
 	}
 }
@@ -121,20 +257,49 @@ from!(string ref str);
 from!(string Vec<u8>);
 from!(string ref [u8]);
 
-macro_rules! define {
+/// Define a capability type.
+///
+/// Boolean and number capabilities wrap their value, string capabilities can
+/// take named and typed parameters, which become builder methods of the given
+/// trait implemented for their `Expansion`, since inherent methods cannot be
+/// added to it outside of this crate. The defined types can be used with
+/// `Database::get` like the standard ones.
+///
+/// ## Example
+///
+/// ```
+/// use terminfo::Database;
+///
+/// terminfo::capability!(boolean HasMv => "HMv");
+/// terminfo::capability!(number MvCount => "Mvc");
+/// terminfo::capability!(string SetMv: SetMvParameters => "Mv";
+///     x: u32,
+///     y: u32);
+///
+/// let mut info = Database::new();
+/// info.name("test").raw("HMv", ()).raw("Mv", "\x1B[%p1%d;%p2%dx");
+/// let info = info.build().unwrap();
+///
+/// assert!(info.get::<HasMv>().unwrap().0);
+/// assert!(info.get::<MvCount>().is_none());
+/// assert_eq!(b"\x1B[4;2x".to_vec(),
+///     info.get::<SetMv>().unwrap().expand().y(2).x(4).to_vec().unwrap());
+/// ```
+#[macro_export]
+macro_rules! capability {
 	(boolean $ident:ident => $capability:expr) => (
 		#[derive(Eq, PartialEq, Copy, Clone, Debug)]
 		pub struct $ident(pub bool);
 
-		impl<'a> Capability<'a> for $ident {
+		impl<'a> $crate::capability::Capability<'a> for $ident {
 			#[inline]
 			fn name() -> &'static str {
 				$capability
 			}
 
 			#[inline]
-			fn from(value: Option<&Value>) -> Option<Self> {
-				if let Some(&Value::True) = value {
+			fn from(value: Option<&$crate::capability::Value>) -> Option<Self> {
+				if let Some(&$crate::capability::Value::True) = value {
 					Some($ident(true))
 				}
 				else {
@@ -143,9 +308,9 @@ macro_rules! define {
 			}
 
 			#[inline]
-			fn into(self) -> Option<Value> {
+			fn into(self) -> Option<$crate::capability::Value> {
 				if self.0 {
-					Some(Value::True)
+					Some($crate::capability::Value::True)
 				}
 				else {
 					None
@@ -164,15 +329,15 @@ macro_rules! define {
 		#[derive(Eq, PartialEq, Copy, Clone, Debug)]
 		pub struct $ident(pub i32);
 
-		impl<'a> Capability<'a> for $ident {
+		impl<'a> $crate::capability::Capability<'a> for $ident {
 			#[inline]
 			fn name() -> &'static str {
 				$capability
 			}
 
 			#[inline]
-			fn from(value: Option<&Value>) -> Option<Self> {
-				if let Some(&Value::Number(value)) = value {
+			fn from(value: Option<&$crate::capability::Value>) -> Option<Self> {
+				if let Some(&$crate::capability::Value::Number(value)) = value {
 					Some($ident(value))
 				}
 				else {
@@ -181,8 +346,8 @@ macro_rules! define {
 			}
 
 			#[inline]
-			fn into(self) -> Option<Value> {
-				Some(Value::Number(self.0))
+			fn into(self) -> Option<$crate::capability::Value> {
+				Some($crate::capability::Value::Number(self.0))
 			}
 		}
 
@@ -193,20 +358,20 @@ macro_rules! define {
 		}
 	);
 
-	(string define $ident:ident => $capability:expr) => (
+	(string define $ident:ident => $capability:expr; $count:expr) => (
 		#[derive(Eq, PartialEq, Clone, Debug)]
-		pub struct $ident<'a>(Cow<'a, [u8]>);
+		pub struct $ident<'a>(::std::borrow::Cow<'a, [u8]>);
 
-		impl<'a> Capability<'a> for $ident<'a> {
+		impl<'a> $crate::capability::Capability<'a> for $ident<'a> {
 			#[inline]
 			fn name() -> &'static str {
 				$capability
 			}
 
 			#[inline]
-			fn from(value: Option<&'a Value>) -> Option<$ident<'a>> {
-				if let Some(&Value::String(ref value)) = value {
-					Some($ident(Cow::Borrowed(value)))
+			fn from(value: Option<&'a $crate::capability::Value>) -> Option<$ident<'a>> {
+				if let Some(&$crate::capability::Value::String(ref value)) = value {
+					Some($ident(::std::borrow::Cow::Borrowed(value)))
 				}
 				else {
 					None
@@ -214,12 +379,12 @@ macro_rules! define {
 			}
 
 			#[inline]
-			fn into(self) -> Option<Value> {
-				Some(Value::String(match self.0 {
-					Cow::Borrowed(value) =>
+			fn into(self) -> Option<$crate::capability::Value> {
+				Some($crate::capability::Value::String(match self.0 {
+					::std::borrow::Cow::Borrowed(value) =>
 						value.into(),
 
-					Cow::Owned(value) =>
+					::std::borrow::Cow::Owned(value) =>
 						value
 				}))
 			}
@@ -228,7 +393,7 @@ macro_rules! define {
 		impl<'a, T: AsRef<&'a [u8]>> From<T> for $ident<'a> {
 			#[inline]
 			fn from(value: T) -> Self {
-				$ident(Cow::Borrowed(value.as_ref()))
+				$ident(::std::borrow::Cow::Borrowed(value.as_ref()))
 			}
 		}
 
@@ -242,22 +407,74 @@ macro_rules! define {
 		impl<'a> $ident<'a> {
 			/// Begin expanding the capability.
 			#[inline]
-			pub fn expand(&self) -> Expansion<'_, $ident<'_>> {
-				Expansion {
-					string:  self,
-					params:  Default::default(),
-					context: None,
-				}
+			pub fn expand(&self) -> $crate::capability::Expansion<'_, $ident<'_>> {
+				$crate::capability::Expansion::new(self, $capability, $count)
 			}
 		}
 	);
 
 	(string $ident:ident => $capability:expr) => (
-		define!(string define $ident => $capability);
+		$crate::capability!(string define $ident => $capability; 0);
 	);
 
+	(string $ident:ident : $parameters:ident => $capability:expr; $($name:ident : $ty:ty),+ $(,)?) => (
+		$crate::capability!(string define $ident => $capability; $crate::capability!(string count $($name : $ty),+));
+
+		/// Expansion parameters of the capability.
+		pub trait $parameters: Sized {
+			/// Pass all expansion parameters at once.
+			#[allow(clippy::too_many_arguments)]
+			fn parameters(self, $($name: $ty),+) -> Self;
+
+			$(
+				/// Set the given parameter.
+				fn $name(self, value: $ty) -> Self;
+			)+
+		}
+
+		impl<'a> $parameters for $crate::capability::Expansion<'a, $ident<'a>> {
+			#[allow(unused_assignments)]
+			#[inline]
+			fn parameters(mut self, $($name: $ty),+) -> Self {
+				let mut index = 0;
+
+				$({
+					self   = self.set(index, $name);
+					index += 1;
+				})+;
+
+				self
+			}
+
+			$crate::capability!(string methods 0; $($name : $ty),+);
+		}
+	);
+
+	(string count $name:ident : $ty:ty) => (
+		1
+	);
+
+	(string count $name:ident : $ty:ty, $($rest:tt)+) => (
+		1 + $crate::capability!(string count $($rest)+)
+	);
+
+	(string methods $index:expr; $name:ident : $ty:ty $(, $rest:ident : $rty:ty)*) => (
+		#[inline]
+		fn $name(self, value: $ty) -> Self {
+			self.set($index, value)
+		}
+
+		$crate::capability!(string methods $index + 1; $($rest : $rty),*);
+	);
+
+	(string methods $index:expr; ) => ();
+}
+
+/// Shorthand for the capabilities defined in this module, where parameters
+/// get inherent builder methods.
+macro_rules! define {
 	(string $ident:ident => $capability:expr; $($rest:tt)+) => (
-		define!(string define $ident => $capability);
+		$crate::capability!(string define $ident => $capability; $crate::capability!(string count $($rest)+));
 		define!(string parameters $ident; $($rest)+);
 		define!(string builder $ident; 0, $($rest)+, );
 	);
@@ -273,8 +490,8 @@ macro_rules! define {
 				let mut index = 0;
 
 				$({
-					self.params[index]  = $name.into();
-					index              += 1;
+					self   = self.set(index, $name);
+					index += 1;
 				})*;
 
 				self
@@ -299,7 +516,7 @@ macro_rules! define {
 		define!(string builder $ident; $index + 1, $($rest)*);
 	);
 
-	(string builder $ident:ident; $index:expr, $name:ident : i16 $($rest:tt)*) => (
+	(string builder $ident:ident; $index:expr, $name:ident : i16, $($rest:tt)*) => (
 		define!(string builder direct $ident; $index, $name : i16);
 		define!(string builder $ident; $index + 1, $($rest)*);
 	);
@@ -323,9 +540,8 @@ macro_rules! define {
 		impl<'a> Expansion<'a, $ident<'a>> {
 			/// Set the given parameter.
 			#[inline]
-			pub fn $name(mut self, value: $ty) -> Self {
-				self.params[$index] = value.into();
-				self
+			pub fn $name(self, value: $ty) -> Self {
+				self.set($index, value)
 			}
 		}
 	);
@@ -334,12 +550,15 @@ macro_rules! define {
 		impl<'a> Expansion<'a, $ident<'a>> {
 			/// Set the given parameter.
 			#[inline]
-			pub fn $name<T: Into<$ty>>(mut self, value: T) -> Self {
-				self.params[$index] = value.into().into();
-				self
+			pub fn $name<T: Into<$ty>>(self, value: T) -> Self {
+				self.set($index, value.into())
 			}
 		}
 	);
+
+	($($tokens:tt)*) => (
+		$crate::capability!($($tokens)*);
+	);
 }
 
 define!(boolean AutoLeftMargin => "auto_left_margin");
@@ -439,7 +658,6 @@ define!(string CursorDown => "cursor_down");
 define!(string CursorHome => "cursor_home");
 define!(string CursorInvisible => "cursor_invisible");
 define!(string CursorLeft => "cursor_left");
-define!(string CursorMemAddress => "cursor_mem_address");
 define!(string CursorNormal => "cursor_normal");
 define!(string CursorRight => "cursor_right");
 define!(string CursorToLl => "cursor_to_ll");
@@ -528,13 +746,9 @@ define!(string MetaOff => "meta_off");
 define!(string MetaOn => "meta_on");
 define!(string Newline => "newline");
 define!(string PadChar => "pad_char");
-define!(string PKeyKey => "pkey_key");
-define!(string PKeyLocal => "pkey_local");
-define!(string PKeyXmit => "pkey_xmit");
 define!(string PrintScreen => "print_screen");
 define!(string PrtrOff => "prtr_off");
 define!(string PrtrOn => "prtr_on");
-define!(string RepeatChar => "repeat_char");
 define!(string Reset1String => "reset_1string");
 define!(string Reset2String => "reset_2string");
 define!(string Reset3String => "reset_3string");
@@ -544,9 +758,7 @@ define!(string SaveCursor => "save_cursor");
 define!(string ScrollForward => "scroll_forward");
 define!(string ScrollReverse => "scroll_reverse");
 define!(string SetTab => "set_tab");
-define!(string SetWindow => "set_window");
 define!(string Tab => "tab");
-define!(string ToStatusLine => "to_status_line");
 define!(string UnderlineChar => "underline_char");
 define!(string UpHalfLine => "up_half_line");
 define!(string InitProg => "init_prog");
@@ -555,10 +767,8 @@ define!(string KeyA3 => "key_a3");
 define!(string KeyB2 => "key_b2");
 define!(string KeyC1 => "key_c1");
 define!(string KeyC3 => "key_c3");
-define!(string PrtrNon => "prtr_non");
 define!(string CharPadding => "char_padding");
 define!(string AcsChars => "acs_chars");
-define!(string PlabNorm => "plab_norm");
 define!(string KeyBTab => "key_btab");
 define!(string EnterXonMode => "enter_xon_mode");
 define!(string ExitXonMode => "exit_xon_mode");
@@ -685,14 +895,9 @@ define!(string ClearMargins => "clear_margins");
 define!(string SetLeftMargin => "set_left_margin");
 define!(string SetRightMargin => "set_right_margin");
 define!(string LabelFormat => "label_format");
-define!(string SetClock => "set_clock");
 define!(string DisplayClock => "display_clock");
 define!(string RemoveClock => "remove_clock");
-define!(string CreateWindow => "create_window");
-define!(string GotoWindow => "goto_window");
 define!(string Hangup => "hangup");
-define!(string DialPhone => "dial_phone");
-define!(string QuickDial => "quick_dial");
 define!(string Tone => "tone");
 define!(string Pulse => "pulse");
 define!(string FlashHook => "flash_hook");
@@ -710,14 +915,6 @@ define!(string User8 => "user8");
 define!(string User9 => "user9");
 define!(string OrigPair => "orig_pair");
 define!(string OrigColors => "orig_colors");
-define!(string InitializeColor => "initialize_color");
-define!(string InitializePair => "initialize_pair");
-define!(string SetColorPair => "set_color_pair");
-define!(string ChangeCharPitch => "change_char_pitch");
-define!(string ChangeLinePitch => "change_line_pitch");
-define!(string ChangeResHorz => "change_res_horz");
-define!(string ChangeResVert => "change_res_vert");
-define!(string DefineChar => "define_char");
 define!(string EnterDoublewideMode => "enter_doublewide_mode");
 define!(string EnterDraftQuality => "enter_draft_quality");
 define!(string EnterItalicsMode => "enter_italics_mode");
@@ -737,51 +934,33 @@ define!(string ExitShadowMode => "exit_shadow_mode");
 define!(string ExitSubscriptMode => "exit_subscript_mode");
 define!(string ExitSuperscriptMode => "exit_superscript_mode");
 define!(string ExitUpwardMode => "exit_upward_mode");
-define!(string MicroColumnAddress => "micro_column_address");
 define!(string MicroDown => "micro_down");
 define!(string MicroLeft => "micro_left");
 define!(string MicroRight => "micro_right");
-define!(string MicroRowAddress => "micro_row_address");
 define!(string MicroUp => "micro_up");
 define!(string OrderOfPins => "order_of_pins");
-define!(string SelectCharSet => "select_char_set");
 define!(string SetBottomMargin => "set_bottom_margin");
-define!(string SetBottomMarginParm => "set_bottom_margin_parm");
-define!(string SetLeftMarginParm => "set_left_margin_parm");
-define!(string SetRightMarginParm => "set_right_margin_parm");
 define!(string SetTopMargin => "set_top_margin");
-define!(string SetTopMarginParm => "set_top_margin_parm");
 define!(string StartBitImage => "start_bit_image");
-define!(string StartCharSetDef => "start_char_set_def");
 define!(string StopBitImage => "stop_bit_image");
-define!(string StopCharSetDef => "stop_char_set_def");
 define!(string SubscriptCharacters => "subscript_characters");
 define!(string SuperscriptCharacters => "superscript_characters");
 define!(string TheseCauseCr => "these_cause_cr");
 define!(string ZeroMotion => "zero_motion");
-define!(string CharSetNames => "char_set_names");
 define!(string KeyMouse => "key_mouse");
 define!(string MouseInfo => "mouse_info");
 define!(string ReqMousePos => "req_mouse_pos");
 define!(string GetMouse => "get_mouse");
-define!(string PkeyPlab => "pkey_plab");
 define!(string DeviceType => "device_type");
 define!(string CodeSetInit => "code_set_init");
 define!(string Set0DesSeq => "set0_des_seq");
 define!(string Set1DesSeq => "set1_des_seq");
 define!(string Set2DesSeq => "set2_des_seq");
 define!(string Set3DesSeq => "set3_des_seq");
-define!(string SetLrMargin => "set_lr_margin");
-define!(string SetTbMargin => "set_tb_margin");
-define!(string BitImageRepeat => "bit_image_repeat");
 define!(string BitImageNewline => "bit_image_newline");
 define!(string BitImageCarriageReturn => "bit_image_carriage_return");
-define!(string ColorNames => "color_names");
 define!(string DefineBitImageRegion => "define_bit_image_region");
 define!(string EndBitImageRegion => "end_bit_image_region");
-define!(string SetColorBand => "set_color_band");
-define!(string SetPageLength => "set_page_length");
-define!(string DisplayPcChar => "display_pc_char");
 define!(string EnterPcCharsetMode => "enter_pc_charset_mode");
 define!(string ExitPcCharsetMode => "exit_pc_charset_mode");
 define!(string EnterScancodeMode => "enter_scancode_mode");
@@ -796,7 +975,6 @@ define!(string EnterRightHlMode => "enter_right_hl_mode");
 define!(string EnterTopHlMode => "enter_top_hl_mode");
 define!(string EnterVerticalHlMode => "enter_vertical_hl_mode");
 define!(string SetAAttributes => "set_a_attributes");
-define!(string SetPglenInch => "set_pglen_inch");
 define!(string TermcapInit2 => "termcap_init2");
 define!(string TermcapReset => "termcap_reset");
 define!(string LinefeedIfNotLf => "linefeed_if_not_lf");
@@ -900,11 +1078,172 @@ define!(string SetForeground => "set_foreground";
 define!(string SetBackground => "set_background";
 	color: u8);
 
+define!(string ChangeCharPitch => "change_char_pitch";
+	pitch: u32);
+
+define!(string ChangeLinePitch => "change_line_pitch";
+	pitch: u32);
+
+define!(string ChangeResHorz => "change_res_horz";
+	resolution: u32);
+
+define!(string ChangeResVert => "change_res_vert";
+	resolution: u32);
+
+define!(string CharSetNames => "char_set_names";
+	index: u32);
+
+define!(string ColorNames => "color_names";
+	color: u32);
+
+define!(string CreateWindow => "create_window";
+	window: u32,
+	top:    u32,
+	left:   u32,
+	bottom: u32,
+	right:  u32);
+
+define!(string CursorMemAddress => "cursor_mem_address";
+	y: u32,
+	x: u32);
+
+define!(string DefineChar => "define_char";
+	character: u32,
+	width:     u32,
+	descender: u32);
+
+define!(string DialPhone => "dial_phone";
+	number: String);
+
+define!(string DisplayPcChar => "display_pc_char";
+	character: u32);
+
+define!(string GotoWindow => "goto_window";
+	window: u32);
+
+define!(string InitializeColor => "initialize_color";
+	color: u32,
+	red:   u32,
+	green: u32,
+	blue:  u32);
+
+define!(string InitializePair => "initialize_pair";
+	pair:     u32,
+	fg_red:   u32,
+	fg_green: u32,
+	fg_blue:  u32,
+	bg_red:   u32,
+	bg_green: u32,
+	bg_blue:  u32);
+
+define!(string MicroColumnAddress => "micro_column_address";
+	x: u32);
+
+define!(string MicroRowAddress => "micro_row_address";
+	y: u32);
+
+define!(string PKeyKey => "pkey_key";
+	key:     u32,
+	command: Vec<u8>);
+
+define!(string PKeyLocal => "pkey_local";
+	key:     u32,
+	command: Vec<u8>);
+
+define!(string PKeyXmit => "pkey_xmit";
+	key:     u32,
+	command: Vec<u8>);
+
+define!(string PkeyPlab => "pkey_plab";
+	key:     u32,
+	command: Vec<u8>,
+	label:   String);
+
+define!(string PlabNorm => "plab_norm";
+	label: u32,
+	text:  String);
+
+define!(string PrtrNon => "prtr_non";
+	count: u32);
+
+define!(string QuickDial => "quick_dial";
+	number: String);
+
+define!(string RepeatChar => "repeat_char";
+	character: u8,
+	count:     u32);
+
+define!(string SelectCharSet => "select_char_set";
+	charset: u32);
+
+define!(string SetBottomMarginParm => "set_bottom_margin_parm";
+	line:        u32,
+	from_bottom: u32);
+
+define!(string SetClock => "set_clock";
+	hours:   u32,
+	minutes: u32,
+	seconds: u32);
+
+define!(string SetColorBand => "set_color_band";
+	color: u32);
+
+define!(string SetColorPair => "set_color_pair";
+	pair: u32);
+
+define!(string SetLeftMarginParm => "set_left_margin_parm";
+	x: u32);
+
+define!(string SetRightMarginParm => "set_right_margin_parm";
+	x: u32);
+
+define!(string SetLrMargin => "set_lr_margin";
+	left:  u32,
+	right: u32);
+
+define!(string SetPageLength => "set_page_length";
+	lines: u32);
+
+define!(string SetPglenInch => "set_pglen_inch";
+	length: u32);
+
+define!(string SetTbMargin => "set_tb_margin";
+	top:    u32,
+	bottom: u32);
+
+define!(string SetTopMarginParm => "set_top_margin_parm";
+	y: u32);
+
+define!(string SetWindow => "set_window";
+	top:    u32,
+	bottom: u32,
+	left:   u32,
+	right:  u32);
+
+define!(string StartCharSetDef => "start_char_set_def";
+	charset: u32,
+	count:   u32);
+
+define!(string StopCharSetDef => "stop_char_set_def";
+	charset: u32);
+
+define!(string ToStatusLine => "to_status_line";
+	x: u32);
+
+define!(string BitImageRepeat => "bit_image_repeat";
+	cell:  u32,
+	count: u32);
+
 // Extended capabilities from screen.
 define!(boolean XTermTitle => "XT");
 define!(boolean BrightAttribute => "AX");
 define!(boolean XTermMouse => "XM");
 
+// Extended capabilities for the window title, like `tsl` and `fsl` without
+// parameters.
+define!(string ToTitle => "TS");
+define!(string FromTitle => "FS");
+
 // Extended capabilities from tmux.
 define!(boolean TrueColor => "Tc");
 
@@ -933,6 +1272,36 @@ define!(string ResetCursorColor => "Cr");
 define!(string SetCursorColor => "Cs";
 	color: String);
 
+// Bracketed paste extended capabilities from ncurses.
+define!(string EnableBracketedPaste => "BE");
+define!(string DisableBracketedPaste => "BD");
+define!(string PasteStart => "PS");
+define!(string PasteEnd => "PE");
+
+// Focus events extended capabilities from ncurses.
+define!(string EnableFocusEvents => "fe");
+define!(string DisableFocusEvents => "fd");
+define!(string KeyFocusIn => "kxIN");
+define!(string KeyFocusOut => "kxOUT");
+
+// Extended capabilities from ncurses, kitty and foot.
+define!(string SynchronizedOutput => "Sync";
+	mode: u8);
+
+define!(string SetUnderlineStyle => "Smulx";
+	kind: u8);
+
+define!(string SetUnderlineColor => "Setulc";
+	color: u32);
+
+define!(string EnterOverlineMode => "Smol");
+define!(string ExitOverlineMode => "Rmol");
+
+define!(string RequestVersion => "XR");
+define!(string VersionResponse => "xr");
+
+define!(string ClearScrollback => "E3");
+
 #[cfg(test)]
 mod test {
 	use super::*;
@@ -992,4 +1361,113 @@ mod test {
 				.unwrap()
 		);
 	}
+
+	#[test]
+	fn extended() {
+		let mut info = Database::new();
+		info.name("test")
+			.raw("BE", "\x1B[?2004h")
+			.raw("Sync", "\x1B[?2026%?%p1%{1}%-%tl%eh%;")
+			.raw("Smulx", "\x1B[4:%p1%dm")
+			.raw("Setulc", "\x1B[58:2::%p1%{65536}%/%d:%p1%{256}%/%{255}%&%d:%p1%{255}%&%dm");
+		let info = info.build().unwrap();
+
+		assert_eq!(
+			b"\x1B[?2004h".to_vec(),
+			info.get::<EnableBracketedPaste>().unwrap().expand().to_vec().unwrap()
+		);
+		assert_eq!(
+			b"\x1B[?2026h".to_vec(),
+			info.get::<SynchronizedOutput>().unwrap().expand().mode(1).to_vec().unwrap()
+		);
+		assert_eq!(
+			b"\x1B[?2026l".to_vec(),
+			info.get::<SynchronizedOutput>().unwrap().expand().mode(2).to_vec().unwrap()
+		);
+		assert_eq!(
+			b"\x1B[4:3m".to_vec(),
+			info.get::<SetUnderlineStyle>().unwrap().expand().kind(3).to_vec().unwrap()
+		);
+		assert_eq!(
+			b"\x1B[58:2::255:128:1m".to_vec(),
+			info.get::<SetUnderlineColor>().unwrap().expand().color(0xFF8001u32).to_vec().unwrap()
+		);
+		assert!(info.get::<ClearScrollback>().is_none());
+	}
+
+	#[test]
+	fn parameters() {
+		let info = Database::from_path("tests/cancer-256color").unwrap();
+		let address = info.get::<CursorAddress>().unwrap();
+
+		assert_eq!(b"\x1B[1;1H".to_vec(), address.expand().to_vec().unwrap());
+		assert_eq!(b"\x1B[3;1H".to_vec(), address.expand().y(2).to_vec().unwrap());
+
+		let info = Database::from_path("tests/xterm-256color").unwrap();
+		let repeat = info.get::<RepeatChar>().unwrap();
+
+		assert_eq!(
+			b"a\x1B[2b".to_vec(),
+			repeat.expand().character(b'a').count(3).to_vec().unwrap()
+		);
+		assert_eq!(b"\0\x1B[-1b".to_vec(), repeat.expand().to_vec().unwrap());
+
+		let value = Value::String(b"%p1%d;%p2%s;%p3%d".to_vec());
+		let raw = <User0 as Capability>::from(Some(&value)).unwrap();
+
+		assert_eq!(b"1;a;3".to_vec(), raw.expand().push(1).push("a").push(3).to_vec().unwrap());
+
+		assert_eq!(
+			b"1;a;3".to_vec(),
+			raw.expand().extend(vec![Parameter::from(1), "a".into()]).set(2, 3).to_vec().unwrap()
+		);
+
+		assert_eq!(
+			"Missing parameter in `user0` at byte 12 (`%p3`).",
+			raw.expand().push(1).push("a").to_vec().unwrap_err().to_string()
+		);
+
+		assert_eq!(b"1;a;3".to_vec(), raw.expand().extend(1..=12).set(1, "a").to_vec().unwrap());
+
+		let value = Value::String(b"%p9%d;%p{10}%d;%p{12}%d".to_vec());
+		let raw = <User0 as Capability>::from(Some(&value)).unwrap();
+
+		assert_eq!(b"9;10;12".to_vec(), raw.expand().extend(1..=12).to_vec().unwrap());
+		assert_eq!(b"0;0;7".to_vec(), raw.expand().set(11, 7).to_vec().unwrap());
+	}
+
+	#[test]
+	fn scratch() {
+		let info = Database::from_path("tests/cancer-256color").unwrap();
+		let address = info.get::<CursorAddress>().unwrap();
+		let mut scratch = Scratch::default();
+		let mut output = Vec::new();
+		let mut string = String::new();
+
+		for i in 0..3 {
+			address.expand().x(i).y(i).scratch(&mut scratch).append(&mut output).unwrap();
+			address.expand().x(i).y(i).scratch(&mut scratch).to_fmt(&mut string).unwrap();
+		}
+
+		assert_eq!(b"\x1B[1;1H\x1B[2;2H\x1B[3;3H".to_vec(), output);
+		assert_eq!("\x1B[1;1H\x1B[2;2H\x1B[3;3H", string);
+	}
+
+	#[test]
+	fn trace() {
+		let info = Database::from_path("tests/cancer-256color").unwrap();
+		let mut params = Vec::new();
+
+		info.get::<CursorAddress>()
+			.unwrap()
+			.expand()
+			.x(1)
+			.y(2)
+			.trace(&mut |step: &expand::Step| params.push(step.after.params[..2].to_vec()))
+			.to_vec()
+			.unwrap();
+
+		assert_eq!(vec![Parameter::from(2), Parameter::from(1)], params[0]);
+		assert_eq!(vec![Parameter::from(3), Parameter::from(2)], params[params.len() - 1]);
+	}
 }
@@ -0,0 +1,253 @@
+//            DO WHAT THE FUCK YOU WANT TO PUBLIC LICENSE
+//                    Version 2, December 2004
+//
+// Copyleft (ↄ) meh. <meh@schizofreni.co> | http://meh.schizofreni.co
+//
+// Everyone is permitted to copy and distribute verbatim or modified
+// copies of this license document, and changing it is allowed as long
+// as the name is changed.
+//
+//            DO WHAT THE FUCK YOU WANT TO PUBLIC LICENSE
+//   TERMS AND CONDITIONS FOR COPYING, DISTRIBUTION AND MODIFICATION
+//
+//  0. You just DO WHAT THE FUCK YOU WANT TO.
+
+//! Clipboard access through OSC 52.
+//!
+//! ## Example
+//!
+//! ```
+//! use terminfo::Database;
+//! use terminfo::clipboard::{Clipboard, Passthrough, Selection};
+//!
+//! let info      = Database::from_path("tests/xterm-256color").unwrap();
+//! let clipboard = Clipboard::new(&info);
+//!
+//! assert_eq!(b"\x1B]52;c;aHVl\x07".to_vec(),
+//!     clipboard.sequence(&[Selection::Clipboard], "hue").unwrap());
+//!
+//! assert_eq!(b"\x1BPtmux;\x1B\x1B]52;p;aHVl\x07\x1B\\".to_vec(),
+//!     clipboard.passthrough(Passthrough::Tmux).sequence(&[Selection::Primary], "hue").unwrap());
+//! ```
+
+use std::io::Write;
+
+use crate::capability::{self as cap, Capability, Value};
+use crate::error;
+use crate::Database;
+
+/// The standard xterm sequence, used when the terminal lacks `Ms`.
+const DEFAULT: &[u8] = b"\x1B]52;%p1%s;%p2%s\x07";
+
+/// The longest string screen forwards at once.
+const SCREEN_CHUNK: usize = 76;
+
+/// A selection target.
+#[derive(Eq, PartialEq, Copy, Clone, Hash, Debug)]
+pub enum Selection {
+	/// The clipboard.
+	Clipboard,
+
+	/// The primary selection.
+	Primary,
+
+	/// The secondary selection.
+	Secondary,
+}
+
+impl Selection {
+	/// The name of the selection in OSC 52.
+	pub fn code(self) -> char {
+		match self {
+			Selection::Clipboard => 'c',
+
+			Selection::Primary => 'p',
+
+			Selection::Secondary => 's',
+		}
+	}
+}
+
+/// A terminal multiplexer to pass the sequence through to the outer terminal.
+#[derive(Eq, PartialEq, Copy, Clone, Hash, Debug)]
+pub enum Passthrough {
+	/// Wrap the sequence in a tmux DCS passthrough.
+	Tmux,
+
+	/// Wrap the sequence in screen DCS strings.
+	Screen,
+}
+
+/// The clipboard of a terminal.
+#[derive(Eq, PartialEq, Clone, Debug)]
+pub struct Clipboard {
+	format: Value,
+	passthrough: Option<Passthrough>,
+}
+
+impl Clipboard {
+	/// Load the clipboard sequence of the given terminal.
+	pub fn new(info: &Database) -> Self {
+		Clipboard {
+			format: info
+				.get::<cap::SetClipboard>()
+				.and_then(Capability::into)
+				.unwrap_or_else(|| Value::String(DEFAULT.to_vec())),
+
+			passthrough: None,
+		}
+	}
+
+	/// Wrap the output to pass it through the given multiplexer.
+	pub fn passthrough(mut self, value: Passthrough) -> Self {
+		self.passthrough = Some(value);
+		self
+	}
+
+	/// The sequence to set the given selections, the clipboard when none is
+	/// given.
+	pub fn sequence<T: AsRef<[u8]>>(
+		&self,
+		selections: &[Selection],
+		content: T,
+	) -> error::Result<Vec<u8>> {
+		let selections = if selections.is_empty() {
+			Selection::Clipboard.code().to_string()
+		} else {
+			selections.iter().map(|selection| selection.code()).collect()
+		};
+
+		let mut sequence = <cap::SetClipboard as Capability>::from(Some(&self.format))
+			.expect("the format is a string")
+			.expand()
+			.selection(selections)
+			.content(encode(content).into_bytes())
+			.to_vec()?;
+
+		Ok(match self.passthrough {
+			None => sequence,
+
+			Some(Passthrough::Tmux) => {
+				let mut output = b"\x1BPtmux;".to_vec();
+
+				for &byte in &sequence {
+					if byte == 0x1B {
+						output.push(0x1B);
+					}
+
+					output.push(byte);
+				}
+
+				output.extend(b"\x1B\\");
+				output
+			}
+
+			Some(Passthrough::Screen) => {
+				// An inner string terminator would end the wrapping early.
+				if sequence.ends_with(b"\x1B\\") {
+					sequence.truncate(sequence.len() - 2);
+					sequence.push(0x07);
+				}
+
+				let mut output = Vec::new();
+				let mut rest = &sequence[..];
+
+				while !rest.is_empty() {
+					let mut length = rest.len().min(SCREEN_CHUNK);
+
+					// Keep escapes together with the byte following them.
+					if length < rest.len() && rest[length - 1] == 0x1B {
+						length -= 1;
+					}
+
+					output.extend(b"\x1BP");
+					output.extend(&rest[..length]);
+					output.extend(b"\x1B\\");
+					rest = &rest[length..];
+				}
+
+				output
+			}
+		})
+	}
+
+	/// Set the given selections, the clipboard when none is given.
+	pub fn set<W: Write, T: AsRef<[u8]>>(
+		&self,
+		mut output: W,
+		selections: &[Selection],
+		content: T,
+	) -> error::Result<()> {
+		output.write_all(&self.sequence(selections, content)?)?;
+		Ok(())
+	}
+}
+
+/// Encode the content in base64 as OSC 52 requires.
+pub fn encode<T: AsRef<[u8]>>(content: T) -> String {
+	const ALPHABET: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+	let content = content.as_ref();
+	let mut output = String::with_capacity(content.len().div_ceil(3) * 4);
+
+	for chunk in content.chunks(3) {
+		let bits = chunk
+			.iter()
+			.enumerate()
+			.fold(0u32, |bits, (i, &byte)| bits | (byte as u32) << (16 - i * 8));
+
+		for i in 0..4 {
+			if i <= chunk.len() {
+				output.push(ALPHABET[(bits >> (18 - i * 6)) as usize & 0x3F] as char);
+			} else {
+				output.push('=');
+			}
+		}
+	}
+
+	output
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+
+	#[test]
+	fn base64() {
+		assert_eq!("", encode(""));
+		assert_eq!("Zg==", encode("f"));
+		assert_eq!("Zm8=", encode("fo"));
+		assert_eq!("Zm9v", encode("foo"));
+		assert_eq!("Zm9vYmFy", encode("foobar"));
+		assert_eq!("/+8=", encode([0xFF, 0xEF]));
+	}
+
+	#[test]
+	fn sequence() {
+		let info = Database::from_path("tests/cancer-256color").unwrap();
+		let clipboard = Clipboard::new(&info);
+
+		assert_eq!(
+			b"\x1B]clipboard:set:ps:aHVl\x07".to_vec(),
+			clipboard.sequence(&[Selection::Primary, Selection::Secondary], "hue").unwrap()
+		);
+
+		let info = Database::from_path("tests/xterm-256color").unwrap();
+		let clipboard = Clipboard::new(&info).passthrough(Passthrough::Screen);
+		let output = clipboard.sequence(&[], "x".repeat(100)).unwrap();
+
+		assert!(output.starts_with(b"\x1BP\x1B]52;c;eHh4"));
+		assert_eq!(2, output.windows(2).filter(|window| window == b"\x1BP").count());
+
+		let mut info = Database::new();
+		info.name("test").raw("Ms", format!("{}\x1B]52;%p1%s;%p2%s\x1B\\", "x".repeat(75)));
+		let info = info.build().unwrap();
+		let clipboard = Clipboard::new(&info).passthrough(Passthrough::Screen);
+		let output = clipboard.sequence(&[], "hue").unwrap();
+
+		assert_eq!(
+			format!("\x1BP{}\x1B\\\x1BP\x1B]52;c;aHVl\x07\x1B\\", "x".repeat(75)).into_bytes(),
+			output
+		);
+	}
+}
@@ -0,0 +1,494 @@
+//            DO WHAT THE FUCK YOU WANT TO PUBLIC LICENSE
+//                    Version 2, December 2004
+//
+// Copyleft (ↄ) meh. <meh@schizofreni.co> | http://meh.schizofreni.co
+//
+// Everyone is permitted to copy and distribute verbatim or modified
+// copies of this license document, and changing it is allowed as long
+// as the name is changed.
+//
+//            DO WHAT THE FUCK YOU WANT TO PUBLIC LICENSE
+//   TERMS AND CONDITIONS FOR COPYING, DISTRIBUTION AND MODIFICATION
+//
+//  0. You just DO WHAT THE FUCK YOU WANT TO.
+
+//! Colors downgraded to what the terminal supports.
+//!
+//! ## Example
+//!
+//! ```
+//! use terminfo::Database;
+//! use terminfo::color::{Color, Colors, Depth};
+//!
+//! let info   = Database::from_path("tests/xterm-256color").unwrap();
+//! let colors = Colors::new(&info);
+//! assert_eq!(Depth::Palette256, colors.depth());
+//!
+//! let mut output = Vec::new();
+//! colors.foreground(&mut output, Color::Rgb(255, 0, 0)).unwrap();
+//! assert_eq!(b"\x1B[38;5;196m".to_vec(), output);
+//! ```
+
+use std::io::Write;
+
+use crate::capability as cap;
+use crate::capability::Value;
+use crate::error;
+use crate::expand::{Context, Expand, Parameter};
+use crate::Database;
+
+/// A color.
+#[derive(Eq, PartialEq, Copy, Clone, Hash, Debug)]
+pub enum Color {
+	/// One of the 16 ANSI colors, `8` to `15` are the bright variants.
+	Ansi(u8),
+
+	/// An index in the 256 colors palette.
+	Indexed(u8),
+
+	/// A direct color.
+	Rgb(u8, u8, u8),
+}
+
+/// The default xterm values of the 16 ANSI colors.
+const ANSI: [(u8, u8, u8); 16] = [
+	(0, 0, 0),
+	(205, 0, 0),
+	(0, 205, 0),
+	(205, 205, 0),
+	(0, 0, 238),
+	(205, 0, 205),
+	(0, 205, 205),
+	(229, 229, 229),
+	(127, 127, 127),
+	(255, 0, 0),
+	(0, 255, 0),
+	(255, 255, 0),
+	(92, 92, 255),
+	(255, 0, 255),
+	(0, 255, 255),
+	(255, 255, 255),
+];
+
+/// The levels of the 256 colors cube.
+const CUBE256: [u8; 6] = [0, 95, 135, 175, 215, 255];
+
+/// The levels of the 88 colors cube.
+const CUBE88: [u8; 4] = [0, 139, 205, 255];
+
+/// The levels of the 88 colors gray ramp.
+const GRAY88: [u8; 8] = [46, 92, 115, 139, 162, 185, 208, 231];
+
+impl Color {
+	/// The RGB value of the color, using the default xterm palette.
+	pub fn rgb(self) -> (u8, u8, u8) {
+		match self {
+			Color::Ansi(index) => ANSI[index as usize & 15],
+
+			Color::Indexed(index @ 0..=15) => ANSI[index as usize],
+
+			Color::Indexed(index @ 16..=231) => {
+				let index = index as usize - 16;
+				(CUBE256[index / 36], CUBE256[index / 6 % 6], CUBE256[index % 6])
+			}
+
+			Color::Indexed(index) => {
+				let level = 8 + (index - 232) * 10;
+				(level, level, level)
+			}
+
+			Color::Rgb(r, g, b) => (r, g, b),
+		}
+	}
+}
+
+/// The color depth of a terminal.
+#[derive(Eq, PartialEq, Ord, PartialOrd, Copy, Clone, Hash, Debug)]
+pub enum Depth {
+	/// No colors.
+	Monochrome,
+
+	/// The 8 ANSI colors.
+	Ansi8,
+
+	/// The 16 ANSI colors.
+	Ansi16,
+
+	/// The 88 colors palette.
+	Palette88,
+
+	/// The 256 colors palette.
+	Palette256,
+
+	/// Direct colors.
+	TrueColor,
+}
+
+impl Depth {
+	/// Detect the color depth of the given terminal.
+	///
+	/// Direct colors are detected through the `Tc` and `RGB` extended
+	/// capabilities, the rest from `max_colors`.
+	pub fn new(info: &Database) -> Self {
+		if info.get::<cap::TrueColor>().is_some_and(|cap| cap.0) || info.raw("RGB").is_some() {
+			return Depth::TrueColor;
+		}
+
+		match info.get::<cap::MaxColors>().map_or(0, |cap| cap.0) {
+			256.. => Depth::Palette256,
+			88.. => Depth::Palette88,
+			16.. => Depth::Ansi16,
+			8.. => Depth::Ansi8,
+			_ => Depth::Monochrome,
+		}
+	}
+
+	/// Downgrade the color to one representable at this depth, `None` if no
+	/// colors are supported.
+	pub fn downgrade(self, color: Color) -> Option<Color> {
+		Some(match (self, color) {
+			(Depth::Monochrome, _) => return None,
+
+			(Depth::TrueColor, color) => color,
+
+			(_, Color::Ansi(index)) | (_, Color::Indexed(index @ 0..=15)) => {
+				Color::Ansi(if self == Depth::Ansi8 { index & 7 } else { index & 15 })
+			}
+
+			(Depth::Palette256, color @ Color::Indexed(_)) => color,
+
+			(Depth::Palette256, color) => Color::Indexed(nearest256(color.rgb())),
+
+			(Depth::Palette88, color) => Color::Indexed(nearest88(color.rgb())),
+
+			(Depth::Ansi16, color) => Color::Ansi(nearest(color.rgb(), &ANSI)),
+
+			(Depth::Ansi8, color) => Color::Ansi(nearest(color.rgb(), &ANSI[..8])),
+		})
+	}
+}
+
+/// The squared distance between two colors.
+fn distance(a: (u8, u8, u8), b: (u8, u8, u8)) -> u32 {
+	let d = |a: u8, b: u8| (i32::from(a) - i32::from(b)).unsigned_abs().pow(2);
+	d(a.0, b.0) + d(a.1, b.1) + d(a.2, b.2)
+}
+
+/// The index of the nearest color.
+fn nearest(rgb: (u8, u8, u8), palette: &[(u8, u8, u8)]) -> u8 {
+	(0..palette.len()).min_by_key(|&index| distance(rgb, palette[index])).unwrap_or(0) as u8
+}
+
+/// The index of the nearest level.
+fn level(value: u8, levels: &[u8]) -> usize {
+	(0..levels.len()).min_by_key(|&index| value.abs_diff(levels[index])).unwrap_or(0)
+}
+
+/// The nearest color in the 256 colors cube or gray ramp.
+fn nearest256(rgb: (u8, u8, u8)) -> u8 {
+	let (r, g, b) = (level(rgb.0, &CUBE256), level(rgb.1, &CUBE256), level(rgb.2, &CUBE256));
+	let cube = (CUBE256[r], CUBE256[g], CUBE256[b]);
+
+	let average = (u16::from(rgb.0) + u16::from(rgb.1) + u16::from(rgb.2)) / 3;
+	let gray = (average.saturating_sub(3) / 10).min(23) as u8;
+	let level = 8 + gray * 10;
+
+	if distance(rgb, (level, level, level)) < distance(rgb, cube) {
+		232 + gray
+	} else {
+		16 + (r * 36 + g * 6 + b) as u8
+	}
+}
+
+/// The nearest color in the 88 colors cube or gray ramp.
+fn nearest88(rgb: (u8, u8, u8)) -> u8 {
+	let (r, g, b) = (level(rgb.0, &CUBE88), level(rgb.1, &CUBE88), level(rgb.2, &CUBE88));
+	let cube = (CUBE88[r], CUBE88[g], CUBE88[b]);
+
+	let average = ((u16::from(rgb.0) + u16::from(rgb.1) + u16::from(rgb.2)) / 3) as u8;
+	let gray = level(average, &GRAY88);
+	let level = GRAY88[gray];
+
+	if distance(rgb, (level, level, level)) < distance(rgb, cube) {
+		80 + gray as u8
+	} else {
+		16 + (r * 16 + g * 4 + b) as u8
+	}
+}
+
+/// Swap the blue and red bits, as used by `set_foreground` and
+/// `set_background`.
+fn bgr(index: u8) -> u8 {
+	(index & !5) | ((index & 1) << 2) | ((index & 4) >> 2)
+}
+
+/// The bits per channel of direct colors packed in a number.
+///
+/// Entries like `xterm-direct` set `RGB` and a `max_colors` past 256, their
+/// `set_a_foreground` and `set_a_background` then take packed colors instead
+/// of palette indices, with only the values below 8 being palette colors.
+#[derive(Eq, PartialEq, Copy, Clone, Hash, Debug)]
+pub struct Direct {
+	/// The bits of the red channel.
+	pub red: u8,
+
+	/// The bits of the green channel.
+	pub green: u8,
+
+	/// The bits of the blue channel.
+	pub blue: u8,
+}
+
+impl Direct {
+	/// Detect the direct color layout of the given terminal.
+	///
+	/// `RGB` can either be a boolean, with the bits split evenly from
+	/// `max_colors`, a number of bits per channel, or the bits for each
+	/// channel separated by `/`.
+	///
+	/// Layouts wider than 24 bits fall back to 8 bits per channel.
+	pub fn new(info: &Database) -> Option<Self> {
+		let colors = info.get::<cap::MaxColors>().map_or(0, |cap| cap.0);
+
+		if colors <= 256 {
+			return None;
+		}
+
+		let even = |bits: u32| {
+			let bits = bits.min(16) as u8;
+			Direct { red: bits, green: bits, blue: bits }
+		};
+
+		let default = even(colors.ilog2() / 3);
+
+		let direct = match info.raw("RGB")? {
+			Value::True => default,
+
+			Value::Number(bits) => even(*bits as u32),
+
+			Value::String(string) => {
+				let bits = String::from_utf8_lossy(string)
+					.split('/')
+					.map(|bits| bits.parse().ok().filter(|&bits| bits <= 16))
+					.collect::<Option<Vec<u8>>>();
+
+				match bits.as_deref() {
+					Some(&[red, green, blue]) => Direct { red, green, blue },
+					_ => default,
+				}
+			}
+		};
+
+		if direct.red + direct.green + direct.blue > 24 {
+			return Some(even(8));
+		}
+
+		Some(direct)
+	}
+
+	/// Pack the color in the layout.
+	///
+	/// Since the values below 8 are palette colors, those are bumped to 8.
+	pub fn pack(&self, r: u8, g: u8, b: u8) -> i32 {
+		fn channel(value: u8, bits: u8) -> u32 {
+			if bits >= 8 {
+				u32::from(value) << (bits - 8)
+			} else {
+				u32::from(value) >> (8 - bits)
+			}
+		}
+
+		let value = channel(r, self.red) << (self.green + self.blue)
+			| channel(g, self.green) << self.blue
+			| channel(b, self.blue);
+
+		value.max(8) as i32
+	}
+}
+
+/// Color setting for a terminal.
+///
+/// Direct colors use `8f` and `8b`, `setrgbf` and `setrgbb`, the packed
+/// colors of direct color entries, or the ISO-8613-6 sequences when only `Tc`
+/// or `RGB` are present. Palette colors use
+/// `set_a_foreground` and `set_a_background`, falling back to the legacy
+/// `set_foreground` and `set_background`.
+#[derive(Debug)]
+pub struct Colors<'a> {
+	info: &'a Database,
+	depth: Depth,
+	direct: Option<Direct>,
+}
+
+macro_rules! set {
+	($name:ident, $truecolor:ident, $rgb:expr, $sgr:expr, $palette:ident, $legacy:ident) => {
+		#[doc = concat!("Write the sequence to set the ", stringify!($name), " color, nothing is")]
+		/// written if the terminal has no colors.
+		pub fn $name<W: Write>(&self, mut output: W, color: Color) -> error::Result<()> {
+			let color = match self.downgrade(color) {
+				Some(color) => color,
+				None => return Ok(()),
+			};
+
+			match color {
+				// Only the first 8 palette colors are available with direct colors.
+				Color::Ansi(index) | Color::Indexed(index)
+					if self.direct.is_none() || index < 8 =>
+				{
+					if let Some(cap) = self.info.get::<cap::$palette>() {
+						cap.expand().color(index).to(output)
+					} else if let Some(cap) = self.info.get::<cap::$legacy>() {
+						cap.expand().color(bgr(index)).to(output)
+					} else {
+						Ok(())
+					}
+				}
+
+				color => {
+					let (r, g, b) = color.rgb();
+
+					if let Some(cap) = self.info.get::<cap::$truecolor>() {
+						cap.expand().r(r).g(g).b(b).to(output)
+					} else if let Some(Value::String(string)) = self.info.raw($rgb) {
+						let parameters =
+							[Parameter::from(r), Parameter::from(g), Parameter::from(b)];
+						string[..].expand(output, &parameters, &mut Context::default())
+					} else if let (Some(direct), Some(cap)) =
+						(self.direct, self.info.get::<cap::$palette>())
+					{
+						cap.expand().set(0, direct.pack(r, g, b)).to(output)
+					} else {
+						write!(output, "\x1B[{};2;{};{};{}m", $sgr, r, g, b)?;
+						Ok(())
+					}
+				}
+			}
+		}
+	};
+}
+
+impl<'a> Colors<'a> {
+	/// Create the color setting for the given terminal, detecting its depth.
+	pub fn new(info: &'a Database) -> Self {
+		Colors { info, depth: Depth::new(info), direct: Direct::new(info) }
+	}
+
+	/// Use the given depth instead of the detected one.
+	pub fn with_depth(mut self, depth: Depth) -> Self {
+		self.depth = depth;
+		self
+	}
+
+	/// The color depth.
+	pub fn depth(&self) -> Depth {
+		self.depth
+	}
+
+	/// Downgrade the color to the terminal depth.
+	pub fn downgrade(&self, color: Color) -> Option<Color> {
+		self.depth.downgrade(color)
+	}
+
+	/// The direct color layout, if `set_a_foreground` and `set_a_background`
+	/// take packed colors.
+	pub fn direct(&self) -> Option<Direct> {
+		self.direct
+	}
+
+	set!(foreground, SetTrueColorForeground, "setrgbf", 38, SetAForeground, SetForeground);
+	set!(background, SetTrueColorBackground, "setrgbb", 48, SetABackground, SetBackground);
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+
+	#[test]
+	fn downgrade() {
+		assert_eq!(Some(Color::Indexed(196)), Depth::Palette256.downgrade(Color::Rgb(255, 0, 0)));
+		assert_eq!(Some(Color::Indexed(232)), Depth::Palette256.downgrade(Color::Rgb(8, 9, 10)));
+		assert_eq!(
+			Some(Color::Indexed(244)),
+			Depth::Palette256.downgrade(Color::Rgb(128, 128, 128))
+		);
+		assert_eq!(Some(Color::Ansi(3)), Depth::Palette256.downgrade(Color::Indexed(3)));
+		assert_eq!(Some(Color::Indexed(64)), Depth::Palette88.downgrade(Color::Rgb(255, 0, 0)));
+		assert_eq!(Some(Color::Ansi(9)), Depth::Ansi16.downgrade(Color::Indexed(196)));
+		assert_eq!(Some(Color::Ansi(1)), Depth::Ansi8.downgrade(Color::Rgb(255, 0, 0)));
+		assert_eq!(Some(Color::Ansi(4)), Depth::Ansi8.downgrade(Color::Ansi(12)));
+		assert_eq!(None, Depth::Monochrome.downgrade(Color::Ansi(1)));
+
+		for index in 16..=255 {
+			assert_eq!(
+				Some(Color::Indexed(index)),
+				Depth::Palette256.downgrade(Color::Rgb(
+					Color::Indexed(index).rgb().0,
+					Color::Indexed(index).rgb().1,
+					Color::Indexed(index).rgb().2
+				))
+			);
+		}
+	}
+
+	#[test]
+	fn sequences() {
+		let info = Database::from_path("tests/cancer-256color").unwrap();
+		let colors = Colors::new(&info);
+		let mut output = Vec::new();
+
+		assert_eq!(Depth::TrueColor, colors.depth());
+		colors.foreground(&mut output, Color::Rgb(1, 2, 3)).unwrap();
+		colors.background(&mut output, Color::Ansi(9)).unwrap();
+		assert_eq!(b"\x1B[38;2;1;2;3m\x1B[101m".to_vec(), output);
+
+		let mut info = Database::new();
+		info.name("legacy").raw("colors", 8).raw("setf", "\x1B[3%p1%dm");
+		let info = info.build().unwrap();
+		let colors = Colors::new(&info);
+		let mut output = Vec::new();
+
+		assert_eq!(Depth::Ansi8, colors.depth());
+		colors.foreground(&mut output, Color::Rgb(255, 0, 0)).unwrap();
+		colors.background(&mut output, Color::Ansi(1)).unwrap();
+		assert_eq!(b"\x1B[34m".to_vec(), output);
+	}
+
+	#[test]
+	fn direct() {
+		let mut info = Database::new();
+		info.name("direct")
+			.raw("colors", 0x1000000)
+			.raw("RGB", ())
+			.raw("setaf", "\x1B[%?%p1%{8}%<%t3%p1%d%e38:2::%p1%{65536}%/%d:%p1%{256}%/%{255}%&%d:%p1%{255}%&%d%;m");
+		let info = info.build().unwrap();
+		let colors = Colors::new(&info);
+		let mut output = Vec::new();
+
+		assert_eq!(Some(Direct { red: 8, green: 8, blue: 8 }), colors.direct());
+		colors.foreground(&mut output, Color::Rgb(1, 2, 3)).unwrap();
+		colors.foreground(&mut output, Color::Ansi(1)).unwrap();
+		colors.foreground(&mut output, Color::Ansi(9)).unwrap();
+		colors.foreground(&mut output, Color::Rgb(0, 0, 0)).unwrap();
+		assert_eq!(
+			b"\x1B[38:2::1:2:3m\x1B[31m\x1B[38:2::255:0:0m\x1B[38:2::0:0:8m".to_vec(),
+			output
+		);
+
+		let mut info = Database::new();
+		info.name("wide").raw("colors", 0x1000000).raw("RGB", "16/16/16");
+		let info = info.build().unwrap();
+
+		assert_eq!(Some(Direct { red: 8, green: 8, blue: 8 }), Direct::new(&info));
+
+		let mut info = Database::new();
+		info.name("wide").raw("colors", i32::MAX).raw("RGB", ());
+		let info = info.build().unwrap();
+
+		assert_eq!(Some(Direct { red: 8, green: 8, blue: 8 }), Direct::new(&info));
+
+		assert_eq!(
+			0x3F << 10 | 0x1F << 5 | 0x0F,
+			Direct { red: 6, green: 5, blue: 5 }.pack(255, 255, 120)
+		);
+	}
+}
@@ -0,0 +1,238 @@
+//            DO WHAT THE FUCK YOU WANT TO PUBLIC LICENSE
+//                    Version 2, December 2004
+//
+// Copyleft (ↄ) meh. <meh@schizofreni.co> | http://meh.schizofreni.co
+//
+// Everyone is permitted to copy and distribute verbatim or modified
+// copies of this license document, and changing it is allowed as long
+// as the name is changed.
+//
+//            DO WHAT THE FUCK YOU WANT TO PUBLIC LICENSE
+//   TERMS AND CONDITIONS FOR COPYING, DISTRIBUTION AND MODIFICATION
+//
+//  0. You just DO WHAT THE FUCK YOU WANT TO.
+
+//! Cursor shape, color and visibility.
+//!
+//! ## Example
+//!
+//! ```
+//! use terminfo::Database;
+//! use terminfo::cursor::{Cursor, Shape, Visibility};
+//!
+//! let info   = Database::from_path("tests/xterm-256color").unwrap();
+//! let cursor = Cursor::new(&info);
+//!
+//! let mut output = Vec::new();
+//! assert!(cursor.visibility(&mut output, Visibility::Invisible).unwrap());
+//! assert_eq!(b"\x1B[?25l".to_vec(), output);
+//!
+//! if cursor.features().shape {
+//!     cursor.shape(&mut output, Shape::SteadyBar).unwrap();
+//! }
+//! ```
+
+use std::io::Write;
+
+use crate::capability as cap;
+use crate::error;
+use crate::Database;
+
+/// A cursor shape, as numbered by the DECSCUSR convention `Ss` follows.
+#[derive(Eq, PartialEq, Copy, Clone, Hash, Debug)]
+pub enum Shape {
+	/// The shape configured in the terminal.
+	Default,
+
+	/// Blinking block.
+	BlinkingBlock,
+
+	/// Steady block.
+	SteadyBlock,
+
+	/// Blinking underline.
+	BlinkingUnderline,
+
+	/// Steady underline.
+	SteadyUnderline,
+
+	/// Blinking bar.
+	BlinkingBar,
+
+	/// Steady bar.
+	SteadyBar,
+}
+
+impl Shape {
+	/// The parameter for `Ss`.
+	pub fn code(self) -> u8 {
+		match self {
+			Shape::Default => 0,
+
+			Shape::BlinkingBlock => 1,
+
+			Shape::SteadyBlock => 2,
+
+			Shape::BlinkingUnderline => 3,
+
+			Shape::SteadyUnderline => 4,
+
+			Shape::BlinkingBar => 5,
+
+			Shape::SteadyBar => 6,
+		}
+	}
+}
+
+/// The visibility of the cursor.
+#[derive(Eq, PartialEq, Copy, Clone, Hash, Debug)]
+pub enum Visibility {
+	/// Hidden, with `cursor_invisible`.
+	Invisible,
+
+	/// Normal, with `cursor_normal`.
+	Normal,
+
+	/// Very visible, with `cursor_visible`, falling back to `cursor_normal`.
+	Visible,
+}
+
+/// The cursor features supported by a terminal.
+#[derive(Eq, PartialEq, Copy, Clone, Default, Debug)]
+pub struct Features {
+	/// The shape can be changed.
+	pub shape: bool,
+
+	/// The shape can be reset.
+	pub reset_shape: bool,
+
+	/// The color can be changed.
+	pub color: bool,
+
+	/// The color can be reset.
+	pub reset_color: bool,
+
+	/// The cursor can be hidden.
+	pub invisible: bool,
+
+	/// The cursor can be shown again.
+	pub visible: bool,
+}
+
+/// The cursor of a terminal.
+///
+/// Operations return whether the terminal supports them.
+#[derive(Clone, Debug)]
+pub struct Cursor<'a> {
+	info: &'a Database,
+}
+
+impl<'a> Cursor<'a> {
+	/// Create the cursor for the given terminal.
+	pub fn new(info: &'a Database) -> Self {
+		Cursor { info }
+	}
+
+	/// The supported features.
+	pub fn features(&self) -> Features {
+		let shape = self.info.get::<cap::SetCursorStyle>().is_some();
+
+		Features {
+			shape,
+			reset_shape: shape || self.info.get::<cap::ResetCursorStyle>().is_some(),
+			color: self.info.get::<cap::SetCursorColor>().is_some(),
+			reset_color: self.info.get::<cap::ResetCursorColor>().is_some(),
+			invisible: self.info.get::<cap::CursorInvisible>().is_some(),
+			visible: self.info.get::<cap::CursorNormal>().is_some()
+				|| self.info.get::<cap::CursorVisible>().is_some(),
+		}
+	}
+
+	/// Change the shape.
+	pub fn shape<W: Write>(&self, output: W, shape: Shape) -> error::Result<bool> {
+		match self.info.get::<cap::SetCursorStyle>() {
+			Some(cap) => cap.expand().kind(shape.code()).to(output).map(|_| true),
+			None => Ok(false),
+		}
+	}
+
+	/// Reset the shape with `Se`, or to the default shape with `Ss`.
+	pub fn reset_shape<W: Write>(&self, output: W) -> error::Result<bool> {
+		match self.info.get::<cap::ResetCursorStyle>() {
+			Some(cap) => cap.expand().to(output).map(|_| true),
+			None => self.shape(output, Shape::Default),
+		}
+	}
+
+	/// Change the color, given as a color name or an X11 color specification.
+	pub fn color<W: Write, T: Into<String>>(&self, output: W, color: T) -> error::Result<bool> {
+		match self.info.get::<cap::SetCursorColor>() {
+			Some(cap) => cap.expand().color(color).to(output).map(|_| true),
+			None => Ok(false),
+		}
+	}
+
+	/// Reset the color.
+	pub fn reset_color<W: Write>(&self, output: W) -> error::Result<bool> {
+		match self.info.get::<cap::ResetCursorColor>() {
+			Some(cap) => cap.expand().to(output).map(|_| true),
+			None => Ok(false),
+		}
+	}
+
+	/// Change the visibility.
+	pub fn visibility<W: Write>(&self, output: W, visibility: Visibility) -> error::Result<bool> {
+		let cap = match visibility {
+			Visibility::Invisible => {
+				self.info.get::<cap::CursorInvisible>().map(|cap| cap.expand().to(output))
+			}
+
+			Visibility::Normal => {
+				self.info.get::<cap::CursorNormal>().map(|cap| cap.expand().to(output))
+			}
+
+			Visibility::Visible => match self.info.get::<cap::CursorVisible>() {
+				Some(cap) => Some(cap.expand().to(output)),
+				None => self.info.get::<cap::CursorNormal>().map(|cap| cap.expand().to(output)),
+			},
+		};
+
+		cap.transpose().map(|done| done.is_some())
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+
+	#[test]
+	fn features() {
+		let info = Database::from_path("tests/cancer-256color").unwrap();
+		let cursor = Cursor::new(&info);
+
+		let mut output = Vec::new();
+		assert!(cursor.shape(&mut output, Shape::BlinkingBar).unwrap());
+		assert!(cursor.reset_shape(&mut output).unwrap());
+		assert!(cursor.color(&mut output, "red").unwrap());
+		assert!(cursor.reset_color(&mut output).unwrap());
+		assert_eq!(b"\x1B[5 q\x1B[2 q\x1B]cursor:bg:red\x07\x1B]cursor:bg:-\x07".to_vec(), output);
+
+		let mut info = Database::new();
+		info.name("test").raw("Ss", "\x1B[%p1%d q").raw("cnorm", "\x1B[?25h");
+		let info = info.build().unwrap();
+		let cursor = Cursor::new(&info);
+
+		assert_eq!(
+			Features { shape: true, reset_shape: true, visible: true, ..Default::default() },
+			cursor.features()
+		);
+
+		let mut output = Vec::new();
+		assert!(cursor.shape(&mut output, Shape::SteadyUnderline).unwrap());
+		assert!(cursor.reset_shape(&mut output).unwrap());
+		assert!(cursor.visibility(&mut output, Visibility::Visible).unwrap());
+		assert!(!cursor.visibility(&mut output, Visibility::Invisible).unwrap());
+		assert!(!cursor.color(&mut output, "red").unwrap());
+		assert_eq!(b"\x1B[4 q\x1B[0 q\x1B[?25h".to_vec(), output);
+	}
+}
@@ -19,9 +19,11 @@ use std::fs::{self, File};
 use std::hash::BuildHasherDefault;
 use std::io::Read;
 use std::path::{Path, PathBuf};
+use std::process::Command;
 
-use crate::capability::{Capability, Value};
+use crate::capability::{self as cap, Capability, Value};
 use crate::error::{self, Error};
+use crate::expand::Scratch;
 use crate::names;
 use crate::parser::compiled;
 
@@ -283,4 +285,196 @@ impl Database {
 
 		self.inner.get(name)
 	}
+
+	/// Iterate over all the capabilities by name.
+	///
+	/// Standard capabilities use their long name, extended capabilities use
+	/// the name they were defined with.
+	///
+	/// ## Example
+	///
+	/// ```
+	/// use terminfo::Database;
+	///
+	/// let info = Database::from_env().unwrap();
+	///
+	/// for (name, value) in info.iter() {
+	///     println!("{}: {:?}", name, value);
+	/// }
+	/// ```
+	pub fn iter(&self) -> impl Iterator<Item = (&str, &Value)> {
+		self.inner.iter().map(|(name, value)| (name.as_str(), value))
+	}
+
+	/// The sequence to initialize the terminal, like `tput init`.
+	///
+	/// The output of `init_prog` and the contents of `init_file` are included.
+	pub fn init_sequence(&self) -> error::Result<Vec<u8>> {
+		self.initialize(false)
+	}
+
+	/// The sequence to reset the terminal, like `tput reset`.
+	///
+	/// The `reset_*` strings are used in place of their `init_*` counterpart
+	/// when present.
+	pub fn reset_sequence(&self) -> error::Result<Vec<u8>> {
+		self.initialize(true)
+	}
+
+	/// Assemble the initialization strings in the order ncurses sends them.
+	fn initialize(&self, reset: bool) -> error::Result<Vec<u8>> {
+		let mut output = Vec::new();
+		let mut scratch = Scratch::default();
+		let columns = match self.get::<cap::Columns>() {
+			Some(cap::Columns(value)) if value > 0 => value as u32,
+			_ => 80,
+		};
+
+		// Expand the capability if present, evaluating to whether it was.
+		macro_rules! put {
+			($cap:ty $(; $($name:ident($value:expr)),+)?) => {
+				match self.get::<$cap>() {
+					Some(cap) => {
+						cap.expand()$($(.$name($value))+)?
+							.scratch(&mut scratch)
+							.append(&mut output)?;
+						true
+					}
+
+					None => false,
+				}
+			};
+		}
+
+		// Expand the reset capability when resetting and present, otherwise
+		// the init one.
+		macro_rules! pick {
+			($reset:ty, $init:ty) => {
+				if !(reset && put!($reset)) {
+					put!($init);
+				}
+			};
+		}
+
+		if let Some(program) = self.get::<cap::InitProg>() {
+			// Like ncurses, run the program through the shell.
+			let program = String::from_utf8_lossy(program.as_ref());
+			output.extend(Command::new("sh").arg("-c").arg(&*program).output()?.stdout);
+		}
+
+		pick!(cap::Reset1String, cap::Init1String);
+		pick!(cap::Reset2String, cap::Init2String);
+
+		// Like tset, `clear_margins` is only used along with `set_left_margin`
+		// and `set_right_margin`.
+		if !put!(cap::SetLrMargin; left(0), right(columns - 1)) {
+			let parm =
+				(self.get::<cap::SetLeftMarginParm>(), self.get::<cap::SetRightMarginParm>());
+			let plain = (
+				self.get::<cap::ClearMargins>(),
+				self.get::<cap::SetLeftMargin>(),
+				self.get::<cap::SetRightMargin>(),
+			);
+
+			if let (Some(left), Some(right)) = parm {
+				left.expand().x(0).scratch(&mut scratch).append(&mut output)?;
+				right.expand().x(columns - 1).scratch(&mut scratch).append(&mut output)?;
+			} else if let (Some(clear), Some(left), Some(right)) = plain {
+				if !put!(cap::CarriageReturn) {
+					output.push(b'\r');
+				}
+
+				clear.expand().scratch(&mut scratch).append(&mut output)?;
+				left.expand().scratch(&mut scratch).append(&mut output)?;
+
+				if !put!(cap::ParmRightCursor; count(columns - 1)) {
+					output.resize(output.len() + columns as usize - 1, b' ');
+				}
+
+				right.expand().scratch(&mut scratch).append(&mut output)?;
+
+				if !put!(cap::CarriageReturn) {
+					output.push(b'\r');
+				}
+			}
+		}
+
+		// Like ncurses, tabs are set every 8 columns unless they already are.
+		let tabs = !matches!(self.get::<cap::InitTabs>(), Some(cap::InitTabs(8)));
+
+		if let (true, Some(clear), Some(set)) =
+			(tabs, self.get::<cap::ClearAllTabs>(), self.get::<cap::SetTab>())
+		{
+			output.push(b'\r');
+			clear.expand().scratch(&mut scratch).append(&mut output)?;
+
+			for _ in (8..columns).step_by(8) {
+				output.resize(output.len() + 8, b' ');
+				set.expand().scratch(&mut scratch).append(&mut output)?;
+			}
+
+			output.push(b'\r');
+		}
+
+		let path = match (reset, self.get::<cap::ResetFile>()) {
+			(true, Some(file)) => Some(String::from_utf8_lossy(file.as_ref()).into_owned()),
+			_ => self
+				.get::<cap::InitFile>()
+				.map(|file| String::from_utf8_lossy(file.as_ref()).into_owned()),
+		};
+
+		if let Some(path) = path {
+			output.extend(fs::read(path)?);
+		}
+
+		pick!(cap::Reset3String, cap::Init3String);
+
+		Ok(output)
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+
+	#[test]
+	fn initialize() {
+		let path = env::temp_dir().join(format!("terminfo-init-{}", std::process::id()));
+		fs::write(&path, "F").unwrap();
+
+		let mut info = Database::new();
+		info.name("test")
+			.raw("cols", 20)
+			.raw("it", 6)
+			.raw("iprog", "printf P")
+			.raw("is1", "A")
+			.raw("is2", "B")
+			.raw("is3", "C")
+			.raw("rs1", "R")
+			.raw("smglr", "\x1B[%i%p1%d;%p2%ds")
+			.raw("tbc", "\x1B[3g")
+			.raw("hts", "\x1BH")
+			.raw("if", path.to_str().unwrap());
+		let info = info.build().unwrap();
+
+		assert_eq!(
+			b"PAB\x1B[1;20s\r\x1B[3g        \x1BH        \x1BH\rFC".to_vec(),
+			info.init_sequence().unwrap()
+		);
+
+		assert_eq!(
+			b"PRB\x1B[1;20s\r\x1B[3g        \x1BH        \x1BH\rFC".to_vec(),
+			info.reset_sequence().unwrap()
+		);
+
+		fs::remove_file(path).unwrap();
+
+		let mut info = Database::new();
+		info.name("test").raw("cols", 4).raw("mgc", "M").raw("smgl", "L").raw("smgr", "R");
+		let info = info.build().unwrap();
+		assert_eq!(b"\rML   R\r".to_vec(), info.init_sequence().unwrap());
+
+		let info = Database::from_path("tests/xterm-256color").unwrap();
+		assert!(!info.init_sequence().unwrap().windows(2).any(|w| w == b"\x1BH"));
+	}
 }
@@ -0,0 +1,703 @@
+//            DO WHAT THE FUCK YOU WANT TO PUBLIC LICENSE
+//                    Version 2, December 2004
+//
+// Copyleft (ↄ) meh. <meh@schizofreni.co> | http://meh.schizofreni.co
+//
+// Everyone is permitted to copy and distribute verbatim or modified
+// copies of this license document, and changing it is allowed as long
+// as the name is changed.
+//
+//            DO WHAT THE FUCK YOU WANT TO PUBLIC LICENSE
+//   TERMS AND CONDITIONS FOR COPYING, DISTRIBUTION AND MODIFICATION
+//
+//  0. You just DO WHAT THE FUCK YOU WANT TO.
+
+//! Disassembler and re-encoder for parametrized strings.
+//!
+//! ## Example
+//!
+//! ```
+//! use terminfo::disassemble;
+//!
+//! let code = disassemble::disassemble(b"%?%p1%{8}%<%t3%p1%d%e38;5;%p1%d%;m").unwrap();
+//!
+//! assert_eq!(code, "\
+//! if p1 < 8 then
+//!     print \"3\"
+//!     print %d p1
+//! else
+//!     print \"38;5;\"
+//!     print %d p1
+//! end
+//! print \"m\"
+//! ");
+//! ```
+
+use std::fmt::Write;
+
+use crate::error;
+use crate::expand;
+pub use crate::parser::expansion::{
+	Binary, Conditional, Constant, Flags, Format, Item, Operation, Print, Unary, Variable,
+};
+
+/// Parse a parametrized string into its items.
+///
+/// Fails with the byte offset of the first item that cannot be parsed.
+pub fn parse(string: &[u8]) -> error::Result<Vec<Item<'_>>> {
+	let mut items = Vec::new();
+	let mut input = string;
+
+	while !input.is_empty() {
+		match crate::parser::expansion::parse(input) {
+			Ok((rest, item)) if rest.len() < input.len() => {
+				input = rest;
+				items.push(item);
+			}
+
+			_ => {
+				let position = string.len() - input.len();
+				return Err(expand::failure(error::Expand::Invalid, position, None, &[]));
+			}
+		}
+	}
+
+	Ok(items)
+}
+
+/// Re-encode the items into a parametrized string.
+///
+/// The output is in canonical form, so it may differ from the original string
+/// (for instance `%:#x` becomes `%#x`), but it expands to the same result.
+pub fn assemble(items: &[Item]) -> Vec<u8> {
+	let mut output = Vec::new();
+
+	for item in items {
+		encode(item, &mut output);
+	}
+
+	output
+}
+
+/// Render a parametrized string as indented pseudo-code.
+pub fn disassemble(input: &[u8]) -> error::Result<String> {
+	let items = parse(input)?;
+	let mut rest = &items[..];
+	let block = block(&mut rest, false)?;
+
+	let mut renderer =
+		Renderer { output: String::new(), level: 0, stack: Vec::new(), temporaries: 0 };
+	renderer.block(&block);
+	renderer.flush(0);
+
+	Ok(renderer.output)
+}
+
+fn encode(item: &Item, output: &mut Vec<u8>) {
+	match *item {
+		Item::String(value) => {
+			for &byte in value {
+				if byte == b'%' {
+					output.extend_from_slice(b"%%");
+				} else {
+					output.push(byte);
+				}
+			}
+		}
+
+		Item::Constant(Constant::Character(ch)) => {
+			output.extend_from_slice(&[b'%', b'\'', ch, b'\''])
+		}
+
+		Item::Constant(Constant::Integer(value)) => {
+			output.extend_from_slice(format!("%{{{}}}", value).as_bytes())
+		}
+
+		Item::Variable(Variable::Length) => output.extend_from_slice(b"%l"),
+
+		Item::Variable(Variable::Push(index @ 0..=8)) => {
+			output.extend_from_slice(format!("%p{}", index + 1).as_bytes())
+		}
+
+		Item::Variable(Variable::Push(index)) => {
+			output.extend_from_slice(format!("%p{{{}}}", index as u16 + 1).as_bytes())
+		}
+
+		Item::Variable(Variable::Set(dynamic, index)) => {
+			output.extend_from_slice(&[b'%', b'P', variable(dynamic, index) as u8])
+		}
+
+		Item::Variable(Variable::Get(dynamic, index)) => {
+			output.extend_from_slice(&[b'%', b'g', variable(dynamic, index) as u8])
+		}
+
+		Item::Operation(Operation::Increment) => output.extend_from_slice(b"%i"),
+
+		Item::Operation(Operation::Unary(Unary::Not)) => output.extend_from_slice(b"%!"),
+
+		Item::Operation(Operation::Unary(Unary::NOT)) => output.extend_from_slice(b"%~"),
+
+		Item::Operation(Operation::Binary(operation)) => {
+			output.extend_from_slice(match operation {
+				Binary::Add => b"%+",
+				Binary::Subtract => b"%-",
+				Binary::Multiply => b"%*",
+				Binary::Divide => b"%/",
+				Binary::Remainder => b"%m",
+
+				Binary::AND => b"%&",
+				Binary::OR => b"%|",
+				Binary::XOR => b"%^",
+
+				Binary::And => b"%A",
+				Binary::Or => b"%O",
+
+				Binary::Equal => b"%=",
+				Binary::Greater => b"%>",
+				Binary::Lesser => b"%<",
+			})
+		}
+
+		Item::Conditional(conditional) => output.extend_from_slice(match conditional {
+			Conditional::If => b"%?",
+			Conditional::Then => b"%t",
+			Conditional::Else => b"%e",
+			Conditional::End => b"%;",
+		}),
+
+		Item::Print(p) => output.extend_from_slice(format(&p).as_bytes()),
+	}
+}
+
+/// Format a print item back to its `printf`-like form.
+fn format(p: &Print) -> String {
+	let mut result = String::from("%");
+
+	// A leading `-` or `+` would be parsed as an operation.
+	if p.flags.left || p.flags.sign {
+		result.push(':');
+	}
+
+	if p.flags.alternate {
+		result.push('#');
+	}
+
+	if p.flags.left {
+		result.push('-');
+	}
+
+	if p.flags.sign {
+		result.push('+');
+	}
+
+	if p.flags.space {
+		result.push(' ');
+	}
+
+	if p.flags.width > 0 {
+		let _ = write!(result, "{}", p.flags.width);
+	}
+
+	if p.flags.precision > 0 {
+		let _ = write!(result, ".{}", p.flags.precision);
+	}
+
+	result.push(match p.format {
+		Format::Chr => 'c',
+		Format::Uni => 'u',
+		Format::Str => 's',
+		Format::Dec => 'd',
+		Format::Oct => 'o',
+		Format::Hex => 'x',
+		Format::HEX => 'X',
+	});
+
+	result
+}
+
+fn variable(dynamic: bool, index: u8) -> char {
+	(if dynamic { b'a' + index } else { b'A' + index }) as char
+}
+
+/// Escape a string the way `infocmp` does.
+fn escape(value: &[u8]) -> String {
+	let mut result = String::new();
+
+	for &byte in value {
+		match byte {
+			0x1B => result.push_str("\\E"),
+			b'\n' => result.push_str("\\n"),
+			b'\r' => result.push_str("\\r"),
+			b'\t' => result.push_str("\\t"),
+			0x08 => result.push_str("\\b"),
+			0x0C => result.push_str("\\f"),
+			b'\\' => result.push_str("\\\\"),
+			b'"' => result.push_str("\\\""),
+			b'^' => result.push_str("\\^"),
+			0x7F => result.push_str("^?"),
+			0x00..=0x1F => {
+				result.push('^');
+				result.push((byte + b'@') as char);
+			}
+			0x80..=0xFF => {
+				let _ = write!(result, "\\{:03o}", byte);
+			}
+			_ => result.push(byte as char),
+		}
+	}
+
+	result
+}
+
+/// A structured parametrized string, with conditionals resolved.
+enum Node<'a> {
+	Item(Item<'a>),
+
+	If { arms: Vec<(Vec<Node<'a>>, Vec<Node<'a>>)>, otherwise: Option<Vec<Node<'a>>> },
+}
+
+/// Collect nodes until the end of the input or, when nested, the next
+/// conditional delimiter, which is left in the input.
+fn block<'a>(input: &mut &[Item<'a>], nested: bool) -> error::Result<Vec<Node<'a>>> {
+	let mut nodes = Vec::new();
+
+	while let Some((&item, rest)) = input.split_first() {
+		match item {
+			Item::Conditional(Conditional::If) => {
+				*input = rest;
+				nodes.push(conditional(input)?);
+			}
+
+			Item::Conditional(_) if nested => break,
+
+			Item::Conditional(_) => return Err(error::Expand::Invalid.into()),
+
+			item => {
+				*input = rest;
+				nodes.push(Node::Item(item));
+			}
+		}
+	}
+
+	Ok(nodes)
+}
+
+/// Collect a conditional, the leading `%?` has already been consumed.
+fn conditional<'a>(input: &mut &[Item<'a>]) -> error::Result<Node<'a>> {
+	let mut arms = Vec::new();
+	let mut otherwise = None;
+
+	loop {
+		let condition = block(input, true)?;
+
+		match input.split_first() {
+			Some((Item::Conditional(Conditional::Then), rest)) => {
+				*input = rest;
+			}
+
+			// The last `%e` branch has no condition.
+			Some((Item::Conditional(Conditional::End), rest)) if !arms.is_empty() => {
+				*input = rest;
+				otherwise = Some(condition);
+				break;
+			}
+
+			None if !arms.is_empty() => {
+				otherwise = Some(condition);
+				break;
+			}
+
+			_ => return Err(error::Expand::Invalid.into()),
+		}
+
+		let body = block(input, true)?;
+		arms.push((condition, body));
+
+		match input.split_first() {
+			Some((Item::Conditional(Conditional::Else), rest)) => {
+				*input = rest;
+			}
+
+			Some((Item::Conditional(Conditional::End), rest)) => {
+				*input = rest;
+				break;
+			}
+
+			// A missing final `%;` is tolerated, like when expanding.
+			None => break,
+
+			_ => return Err(error::Expand::Invalid.into()),
+		}
+	}
+
+	Ok(Node::If { arms, otherwise })
+}
+
+/// Whether the nodes only operate on the stack, without printing or setting
+/// anything.
+fn pure(nodes: &[Node]) -> bool {
+	nodes.iter().all(|node| {
+		matches!(
+			*node,
+			Node::Item(
+				Item::Constant(..)
+					| Item::Variable(Variable::Length | Variable::Push(..) | Variable::Get(..))
+					| Item::Operation(Operation::Unary(..) | Operation::Binary(..))
+			)
+		)
+	})
+}
+
+/// An expression recovered from the stack operations.
+enum Expr {
+	Parameter(u8),
+	Character(u8),
+	Integer(i32),
+	Variable(bool, u8),
+	Length(Box<Expr>),
+	Unary(Unary, Box<Expr>),
+	Binary(Binary, Box<Expr>, Box<Expr>),
+
+	/// A value saved before its operands changed.
+	Temporary(usize),
+
+	/// A value pushed outside of the current block.
+	Pop,
+}
+
+impl Expr {
+	/// Whether the expression reads a value matching the predicate.
+	fn depends<F: Fn(&Expr) -> bool>(&self, predicate: &F) -> bool {
+		predicate(self)
+			|| match *self {
+				Expr::Length(ref value) | Expr::Unary(_, ref value) => value.depends(predicate),
+
+				Expr::Binary(_, ref x, ref y) => x.depends(predicate) || y.depends(predicate),
+
+				_ => false,
+			}
+	}
+
+	fn render(&self, output: &mut String) {
+		match *self {
+			Expr::Parameter(index) => {
+				let _ = write!(output, "p{}", index as u16 + 1);
+			}
+
+			Expr::Character(b'\'') => output.push_str("'\\''"),
+
+			Expr::Character(ch) => {
+				let _ = write!(output, "'{}'", escape(&[ch]));
+			}
+
+			Expr::Integer(value) => {
+				let _ = write!(output, "{}", value);
+			}
+
+			Expr::Variable(dynamic, index) => output.push(variable(dynamic, index)),
+
+			Expr::Length(ref value) => {
+				output.push_str("len(");
+				value.render(output);
+				output.push(')');
+			}
+
+			Expr::Unary(operation, ref value) => {
+				output.push(match operation {
+					Unary::Not => '!',
+					Unary::NOT => '~',
+				});
+
+				value.operand(output);
+			}
+
+			Expr::Binary(operation, ref x, ref y) => {
+				x.operand(output);
+
+				output.push_str(match operation {
+					Binary::Add => " + ",
+					Binary::Subtract => " - ",
+					Binary::Multiply => " * ",
+					Binary::Divide => " / ",
+					Binary::Remainder => " % ",
+
+					Binary::AND => " & ",
+					Binary::OR => " | ",
+					Binary::XOR => " ^ ",
+
+					Binary::And => " && ",
+					Binary::Or => " || ",
+
+					Binary::Equal => " == ",
+					Binary::Greater => " > ",
+					Binary::Lesser => " < ",
+				});
+
+				y.operand(output);
+			}
+
+			Expr::Temporary(index) => {
+				let _ = write!(output, "t{}", index);
+			}
+
+			Expr::Pop => output.push_str("pop()"),
+		}
+	}
+
+	/// Render as operand of another operation, parenthesizing if needed.
+	fn operand(&self, output: &mut String) {
+		if let Expr::Binary(..) = *self {
+			output.push('(');
+			self.render(output);
+			output.push(')');
+		} else {
+			self.render(output);
+		}
+	}
+}
+
+struct Renderer {
+	output: String,
+	level: usize,
+	stack: Vec<Expr>,
+	temporaries: usize,
+}
+
+impl Renderer {
+	fn line(&mut self, line: &str) {
+		for _ in 0..self.level {
+			self.output.push_str("    ");
+		}
+
+		self.output.push_str(line);
+		self.output.push('\n');
+	}
+
+	fn pop(&mut self) -> Expr {
+		self.stack.pop().unwrap_or(Expr::Pop)
+	}
+
+	fn expr(&mut self) -> String {
+		let mut result = String::new();
+		self.pop().render(&mut result);
+		result
+	}
+
+	/// Save the stacked values matching the predicate into temporaries, so
+	/// they keep the value they had when pushed.
+	fn snapshot<F: Fn(&Expr) -> bool>(&mut self, predicate: F) {
+		for index in 0..self.stack.len() {
+			if !self.stack[index].depends(&predicate) {
+				continue;
+			}
+
+			self.temporaries += 1;
+			let temporary = Expr::Temporary(self.temporaries);
+			let value = std::mem::replace(&mut self.stack[index], temporary);
+
+			let mut line = format!("t{} = ", self.temporaries);
+			value.render(&mut line);
+			self.line(&line);
+		}
+	}
+
+	/// Emit any value left on the stack above `depth` as explicit pushes.
+	fn flush(&mut self, depth: usize) {
+		if self.stack.len() > depth {
+			for value in self.stack.split_off(depth) {
+				let mut line = String::from("push ");
+				value.render(&mut line);
+				self.line(&line);
+			}
+		}
+	}
+
+	fn block(&mut self, nodes: &[Node]) {
+		for node in nodes {
+			match *node {
+				Node::Item(item) => self.item(item),
+
+				Node::If { ref arms, ref otherwise } => self.conditional(arms, otherwise),
+			}
+		}
+	}
+
+	fn conditional(&mut self, arms: &[(Vec<Node>, Vec<Node>)], otherwise: &Option<Vec<Node>>) {
+		for (index, (condition, body)) in arms.iter().enumerate() {
+			// A condition with side effects cannot go on an `else if` line, so
+			// the remaining arms are nested within an `else` block.
+			if index > 0 && !pure(condition) {
+				self.line("else");
+
+				self.level += 1;
+				let depth = self.stack.len();
+				self.conditional(&arms[index..], otherwise);
+				self.flush(depth);
+				self.level -= 1;
+
+				self.line("end");
+				return;
+			}
+
+			self.block(condition);
+			let condition = self.expr();
+
+			if index == 0 {
+				self.line(&format!("if {} then", condition));
+			} else {
+				self.line(&format!("else if {} then", condition));
+			}
+
+			self.level += 1;
+			let depth = self.stack.len();
+			self.block(body);
+			self.flush(depth);
+			self.level -= 1;
+		}
+
+		if let Some(otherwise) = otherwise {
+			self.line("else");
+
+			self.level += 1;
+			let depth = self.stack.len();
+			self.block(otherwise);
+			self.flush(depth);
+			self.level -= 1;
+		}
+
+		self.line("end");
+	}
+
+	fn item(&mut self, item: Item) {
+		match item {
+			Item::String(value) => self.line(&format!("print \"{}\"", escape(value))),
+
+			Item::Constant(Constant::Character(ch)) => self.stack.push(Expr::Character(ch)),
+
+			Item::Constant(Constant::Integer(value)) => self.stack.push(Expr::Integer(value)),
+
+			Item::Variable(Variable::Length) => {
+				let value = self.pop();
+				self.stack.push(Expr::Length(Box::new(value)));
+			}
+
+			Item::Variable(Variable::Push(index)) => self.stack.push(Expr::Parameter(index)),
+
+			Item::Variable(Variable::Set(dynamic, index)) => {
+				let value = self.expr();
+				self.snapshot(
+					|expr| matches!(*expr, Expr::Variable(d, i) if (d, i) == (dynamic, index)),
+				);
+				self.line(&format!("{} = {}", variable(dynamic, index), value));
+			}
+
+			Item::Variable(Variable::Get(dynamic, index)) => {
+				self.stack.push(Expr::Variable(dynamic, index))
+			}
+
+			Item::Operation(Operation::Increment) => {
+				self.snapshot(|expr| matches!(*expr, Expr::Parameter(0 | 1)));
+				self.line("increment p1, p2");
+			}
+
+			Item::Operation(Operation::Unary(operation)) => {
+				let value = self.pop();
+				self.stack.push(Expr::Unary(operation, Box::new(value)));
+			}
+
+			Item::Operation(Operation::Binary(operation)) => {
+				let y = self.pop();
+				let x = self.pop();
+				self.stack.push(Expr::Binary(operation, Box::new(x), Box::new(y)));
+			}
+
+			Item::Conditional(..) => unreachable!("conditionals are resolved into nodes"),
+
+			Item::Print(p) => {
+				let value = self.expr();
+				self.line(&format!("print {} {}", format(&p), value));
+			}
+		}
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+
+	#[test]
+	fn roundtrip() {
+		for string in [
+			&b"\x1B[%i%p1%d;%p2%dH"[..],
+			b"\x1B[%?%p1%{8}%<%t3%p1%d%e%p1%{16}%<%t9%p1%{8}%-%d%e38;5;%p1%d%;m",
+			b"%?%p9%t\x1B(0%e\x1B(B%;\x1B[0%?%p6%t;1%;%?%p2%t;4%;m",
+			b"100%% %p1%:-3.2x %'a'%PA%gA%l%~",
+			b"%p{10}%d%p{12}%s",
+		] {
+			assert_eq!(string, &assemble(&parse(string).unwrap())[..]);
+		}
+	}
+
+	#[test]
+	fn nested() {
+		assert_eq!(
+			"\
+if p1 then
+    if p2 > 7 then
+        print \"a\"
+    end
+else if p3 then
+    print %s p4
+else
+    a = (p1 + 1) * 2
+end
+",
+			disassemble(b"%?%p1%t%?%p2%{7}%>%ta%;%e%p3%t%p4%s%e%p1%{1}%+%{2}%*%Pa%;").unwrap()
+		);
+	}
+
+	#[test]
+	fn effects() {
+		assert_eq!(
+			"\
+if p1 then
+    print \"A\"
+else
+    print \"B\"
+    if p2 then
+        print \"2\"
+    end
+end
+",
+			disassemble(b"%?%p1%tA%eB%p2%t2%;").unwrap()
+		);
+
+		assert_eq!("print %c '\\''\n", disassemble(b"%'''%c").unwrap());
+	}
+
+	#[test]
+	fn leftovers() {
+		assert_eq!("print \"\\E[\"\npush p1\n", disassemble(b"\x1B[%p1").unwrap());
+		assert_eq!("print %d pop()\n", disassemble(b"%d").unwrap());
+	}
+
+	#[test]
+	fn snapshot() {
+		assert_eq!("t1 = a\na = p1\nprint %d t1\n", disassemble(b"%ga%p1%Pa%d").unwrap());
+		assert_eq!("t1 = p1\nincrement p1, p2\nprint %d t1\n", disassemble(b"%p1%i%d").unwrap());
+		assert_eq!(
+			"t1 = p1 + 1\nincrement p1, p2\nprint %d t1\nprint %d p1\n",
+			disassemble(b"%p1%{1}%+%i%d%p1%d").unwrap()
+		);
+	}
+
+	#[test]
+	fn invalid() {
+		assert!(parse(b"%z").is_err());
+		assert_eq!(
+			"The expansion string is invalid at byte 3.",
+			parse(b"\x1B[?%[;0123456789]c").unwrap_err().to_string()
+		);
+		assert!(disassemble(b"%t").is_err());
+	}
+}
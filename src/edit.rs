@@ -0,0 +1,220 @@
+//            DO WHAT THE FUCK YOU WANT TO PUBLIC LICENSE
+//                    Version 2, December 2004
+//
+// Copyleft (ↄ) meh. <meh@schizofreni.co> | http://meh.schizofreni.co
+//
+// Everyone is permitted to copy and distribute verbatim or modified
+// copies of this license document, and changing it is allowed as long
+// as the name is changed.
+//
+//            DO WHAT THE FUCK YOU WANT TO PUBLIC LICENSE
+//   TERMS AND CONDITIONS FOR COPYING, DISTRIBUTION AND MODIFICATION
+//
+//  0. You just DO WHAT THE FUCK YOU WANT TO.
+
+//! Character insertion, deletion, erasure and repetition.
+//!
+//! ## Example
+//!
+//! ```
+//! use terminfo::Database;
+//! use terminfo::edit::Edit;
+//!
+//! let info = Database::from_path("tests/xterm-256color").unwrap();
+//! let edit = Edit::new(&info);
+//!
+//! assert_eq!(Some(b"\x1B[3@".to_vec()), edit.insert_chars(3).unwrap());
+//! assert_eq!(Some(b"\x1B[P".to_vec()), edit.delete_chars(1).unwrap());
+//! assert_eq!(b"-\x1B[19b".to_vec(), edit.repeat('-', 20).unwrap());
+//! ```
+
+use crate::capability as cap;
+use crate::error;
+use crate::expand::Scratch;
+use crate::Database;
+
+/// Expand the capability if present, with the given parameters.
+macro_rules! expand {
+	($info:expr, $scratch:expr, $cap:ty $(; $($name:ident($value:expr)),+)?) => {
+		$info
+			.get::<$cap>()
+			.map(|cap| cap.expand()$($(.$name($value))+)?.scratch(&mut *$scratch).to_vec())
+			.transpose()?
+	};
+}
+
+/// Character editing of a terminal.
+///
+/// The cursor is left in place and the cheapest of the available capabilities
+/// is used.
+#[derive(Clone, Debug)]
+pub struct Edit<'a> {
+	info: &'a Database,
+	mir: bool,
+}
+
+impl<'a> Edit<'a> {
+	/// Create the character editing for the given terminal.
+	pub fn new(info: &'a Database) -> Self {
+		Edit { info, mir: info.get::<cap::MoveInsertMode>().is_some_and(|cap| cap.0) }
+	}
+
+	/// Insert blank characters, pushing the following ones right.
+	///
+	/// Returns `None` if the terminal cannot insert characters, in which case
+	/// the rest of the line has to be redrawn.
+	pub fn insert_chars(&self, count: u16) -> error::Result<Option<Vec<u8>>> {
+		if count == 0 {
+			return Ok(Some(Vec::new()));
+		}
+
+		let mut scratch = Scratch::default();
+		let scratch = &mut scratch;
+
+		let parm = expand!(self.info, scratch, cap::ParmIch; count(count.into()));
+		let padding = expand!(self.info, scratch, cap::InsertPadding).unwrap_or_default();
+		let single = expand!(self.info, scratch, cap::InsertCharacter).map(|mut single| {
+			single.extend(&padding);
+			single.repeat(count as usize)
+		});
+
+		let enter = expand!(self.info, scratch, cap::EnterInsertMode);
+		let exit = expand!(self.info, scratch, cap::ExitInsertMode);
+		let mode = match (enter, exit) {
+			(Some(mut output), Some(exit)) => {
+				let back = self.back(scratch, count)?;
+
+				for _ in 0..count {
+					output.push(b' ');
+					output.extend(&padding);
+				}
+
+				// Leave insert mode as late as possible when moving in it is safe.
+				if self.mir {
+					output.extend(back);
+					output.extend(exit);
+				} else {
+					output.extend(exit);
+					output.extend(back);
+				}
+
+				Some(output)
+			}
+
+			_ => None,
+		};
+
+		Ok(cheapest([parm, single, mode]))
+	}
+
+	/// Delete characters, pulling the following ones left.
+	///
+	/// Returns `None` if the terminal cannot delete characters, in which case
+	/// the rest of the line has to be redrawn.
+	pub fn delete_chars(&self, count: u16) -> error::Result<Option<Vec<u8>>> {
+		if count == 0 {
+			return Ok(Some(Vec::new()));
+		}
+
+		let mut scratch = Scratch::default();
+		let scratch = &mut scratch;
+
+		let parm = expand!(self.info, scratch, cap::ParmDch; count(count.into()));
+		let single = expand!(self.info, scratch, cap::DeleteCharacter)
+			.map(|single| single.repeat(count as usize));
+
+		Ok(cheapest([parm, single]))
+	}
+
+	/// Erase characters, leaving the following ones in place.
+	///
+	/// The cells are overwritten with blanks when `erase_chars` is missing.
+	pub fn erase_chars(&self, count: u16) -> error::Result<Vec<u8>> {
+		if count == 0 {
+			return Ok(Vec::new());
+		}
+
+		let mut scratch = Scratch::default();
+		let scratch = &mut scratch;
+
+		if let Some(erase) = expand!(self.info, scratch, cap::EraseChars; count(count.into())) {
+			return Ok(erase);
+		}
+
+		let mut output = b" ".repeat(count as usize);
+		output.extend(self.back(scratch, count)?);
+
+		Ok(output)
+	}
+
+	/// Repeat a character, moving the cursor past it.
+	///
+	/// The `repeat_char` capability is only used for ASCII characters.
+	pub fn repeat(&self, ch: char, count: u16) -> error::Result<Vec<u8>> {
+		let mut plain = Vec::new();
+
+		for _ in 0..count {
+			plain.extend(ch.encode_utf8(&mut [0; 4]).as_bytes());
+		}
+
+		if !ch.is_ascii() || count == 0 {
+			return Ok(plain);
+		}
+
+		let mut scratch = Scratch::default();
+		let scratch = &mut scratch;
+
+		match expand!(self.info, scratch, cap::RepeatChar; character(ch as u8), count(count.into()))
+		{
+			Some(repeat) if repeat.len() < plain.len() => Ok(repeat),
+			_ => Ok(plain),
+		}
+	}
+
+	/// Move the cursor left.
+	fn back(&self, scratch: &mut Scratch, count: u16) -> error::Result<Vec<u8>> {
+		let single =
+			expand!(self.info, scratch, cap::CursorLeft).unwrap_or_else(|| b"\x08".to_vec());
+		let repeated = single.repeat(count as usize);
+
+		match expand!(self.info, scratch, cap::ParmLeftCursor; count(count.into())) {
+			Some(parm) if parm.len() < repeated.len() => Ok(parm),
+			_ => Ok(repeated),
+		}
+	}
+}
+
+/// Pick the shortest candidate.
+fn cheapest<const N: usize>(candidates: [Option<Vec<u8>>; N]) -> Option<Vec<u8>> {
+	candidates.into_iter().flatten().min_by_key(Vec::len)
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+
+	#[test]
+	fn capabilities() {
+		let info = Database::from_path("tests/xterm-256color").unwrap();
+		let edit = Edit::new(&info);
+
+		assert_eq!(Some(b"".to_vec()), edit.insert_chars(0).unwrap());
+		assert_eq!(Some(b"\x1B[P".to_vec()), edit.delete_chars(1).unwrap());
+		assert_eq!(Some(b"\x1B[12P".to_vec()), edit.delete_chars(12).unwrap());
+		assert_eq!(b"\x1B[4X".to_vec(), edit.erase_chars(4).unwrap());
+		assert_eq!(b"aaa".to_vec(), edit.repeat('a', 3).unwrap());
+		assert_eq!("ééééééééé".as_bytes().to_vec(), edit.repeat('é', 9).unwrap());
+	}
+
+	#[test]
+	fn fallback() {
+		let mut info = Database::new();
+		info.name("test").raw("smir", "\x1B[4h").raw("rmir", "\x1B[4l").raw("ip", "$");
+		let info = info.build().unwrap();
+		let edit = Edit::new(&info);
+
+		assert_eq!(Some(b"\x1B[4h $ $\x1B[4l\x08\x08".to_vec()), edit.insert_chars(2).unwrap());
+		assert_eq!(None, edit.delete_chars(2).unwrap());
+		assert_eq!(b"   \x08\x08\x08".to_vec(), edit.erase_chars(3).unwrap());
+	}
+}
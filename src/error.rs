@@ -16,6 +16,8 @@ use std::error;
 use std::fmt;
 use std::io;
 
+use crate::expand::Parameter;
+
 #[derive(Debug)]
 pub enum Error {
 	/// IO error.
@@ -28,7 +30,7 @@ pub enum Error {
 	Parse,
 
 	/// Expansion error.
-	Expand(Expand),
+	Expand(Expansion),
 }
 
 #[derive(Eq, PartialEq, Copy, Clone, Debug)]
@@ -41,6 +43,28 @@ pub enum Expand {
 
 	/// The stack underflowed while expanding.
 	StackUnderflow,
+
+	/// A parameter that was not provided was referenced while expanding.
+	MissingParameter,
+}
+
+/// Details of an expansion error.
+#[derive(Eq, PartialEq, Clone, Debug)]
+pub struct Expansion {
+	/// The kind of error.
+	pub kind: Expand,
+
+	/// The name of the capability being expanded, if known.
+	pub capability: Option<&'static str>,
+
+	/// The byte offset of the failing item within the string.
+	pub position: Option<usize>,
+
+	/// The failing item, re-encoded.
+	pub item: Option<String>,
+
+	/// The stack contents before the failing item was executed.
+	pub stack: Vec<Parameter>,
 }
 
 pub type Result<T> = ::std::result::Result<T, Error>;
@@ -53,6 +77,18 @@ impl From<io::Error> for Error {
 
 impl From<Expand> for Error {
 	fn from(value: Expand) -> Self {
+		Error::Expand(value.into())
+	}
+}
+
+impl From<Expand> for Expansion {
+	fn from(value: Expand) -> Self {
+		Expansion { kind: value, capability: None, position: None, item: None, stack: Vec::new() }
+	}
+}
+
+impl From<Expansion> for Error {
+	fn from(value: Expansion) -> Self {
 		Error::Expand(value)
 	}
 }
@@ -66,14 +102,55 @@ impl fmt::Display for Error {
 
 			Error::Parse => f.write_str("Failed to parse capability database."),
 
-			Error::Expand(ref err) => match *err {
-				Expand::Invalid => f.write_str("The expansion string is invalid."),
+			Error::Expand(ref err) => err.fmt(f),
+		}
+	}
+}
+
+impl fmt::Display for Expansion {
+	fn fmt(&self, f: &mut fmt::Formatter) -> ::std::result::Result<(), fmt::Error> {
+		f.write_str(match self.kind {
+			Expand::Invalid => "The expansion string is invalid",
+
+			Expand::StackUnderflow => "Not enough elements on the stack",
+
+			Expand::TypeMismatch => "Type mismatch",
+
+			Expand::MissingParameter => "Missing parameter",
+		})?;
+
+		if let Some(name) = self.capability {
+			write!(f, " in `{}`", name)?;
+		}
+
+		if let Some(position) = self.position {
+			write!(f, " at byte {}", position)?;
+		}
+
+		if let Some(ref item) = self.item {
+			write!(f, " (`{}`)", item)?;
+		}
+
+		if !self.stack.is_empty() {
+			f.write_str(" with stack [")?;
 
-				Expand::StackUnderflow => f.write_str("Not enough elements on the stack."),
+			for (index, value) in self.stack.iter().enumerate() {
+				if index > 0 {
+					f.write_str(", ")?;
+				}
 
-				Expand::TypeMismatch => f.write_str("Type mismatch."),
-			},
+				match *value {
+					Parameter::Number(value) => write!(f, "{}", value)?,
+					Parameter::String(ref value) => {
+						write!(f, "{:?}", String::from_utf8_lossy(value))?
+					}
+				}
+			}
+
+			f.write_str("]")?;
 		}
+
+		f.write_str(".")
 	}
 }
 
@@ -13,19 +13,92 @@
 //  0. You just DO WHAT THE FUCK YOU WANT TO.
 
 use std::char;
-use std::io::{BufWriter, Write};
+use std::fmt;
+use std::io::{self, BufWriter, Write};
+use std::mem;
+use std::str;
 
+use crate::disassemble;
 use crate::error;
 use crate::parser::expansion::*;
 
 /// Trait for items that can be expanded.
 pub trait Expand {
+	/// Expand to the given output, the writes are buffered.
 	fn expand<W: Write>(
 		&self,
 		output: W,
 		parameters: &[Parameter],
 		context: &mut Context,
 	) -> error::Result<()>;
+
+	/// Expand directly to the given output reusing the given scratch space.
+	///
+	/// By default the scratch space is ignored and the expansion is buffered.
+	fn expand_with<W: Write>(
+		&self,
+		output: W,
+		parameters: &[Parameter],
+		context: &mut Context,
+		scratch: &mut Scratch,
+	) -> error::Result<()> {
+		let _ = scratch;
+		self.expand(output, parameters, context)
+	}
+
+	/// Expand to the given formatter reusing the given scratch space.
+	///
+	/// Fails if the expansion is not valid UTF-8.
+	fn expand_fmt<W: fmt::Write + ?Sized>(
+		&self,
+		output: &mut W,
+		parameters: &[Parameter],
+		context: &mut Context,
+		scratch: &mut Scratch,
+	) -> error::Result<()> {
+		format(output, scratch, |buffer, scratch| {
+			self.expand_with(buffer, parameters, context, scratch)
+		})
+	}
+
+	/// Expand directly to the given output reporting every executed item to
+	/// the observer.
+	///
+	/// By default the observer is ignored.
+	fn expand_traced<W: Write>(
+		&self,
+		output: W,
+		parameters: &[Parameter],
+		context: &mut Context,
+		scratch: &mut Scratch,
+		observer: &mut dyn Observer,
+	) -> error::Result<()> {
+		let _ = observer;
+		self.expand_with(output, parameters, context, scratch)
+	}
+}
+
+/// Run the given expansion through the scratch buffer and write it to the
+/// formatter.
+pub(crate) fn format<W, F>(output: &mut W, scratch: &mut Scratch, f: F) -> error::Result<()>
+where
+	W: fmt::Write + ?Sized,
+	F: FnOnce(&mut Vec<u8>, &mut Scratch) -> error::Result<()>,
+{
+	let mut buffer = mem::take(&mut scratch.buffer);
+	buffer.clear();
+
+	let result = f(&mut buffer, scratch).and_then(|()| {
+		let string = str::from_utf8(&buffer)
+			.map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+
+		output.write_str(string).map_err(io::Error::other)?;
+
+		Ok(())
+	});
+
+	scratch.buffer = buffer;
+	result
 }
 
 /// An expansion parameter.
@@ -87,12 +160,84 @@ from!(string ref [u8]);
 ///
 /// The same context should be passed around through every expansion for the
 /// same `Database`.
-#[derive(Eq, PartialEq, Default, Debug)]
+#[derive(Eq, PartialEq, Clone, Default, Debug)]
 pub struct Context {
 	pub fixed: [Parameter; 26],
 	pub dynamic: [Parameter; 26],
 }
 
+/// Reusable scratch space for expansions.
+///
+/// Passing the same scratch space to every expansion reuses its
+/// allocations, so that once warmed up expanding numeric parameters doesn't
+/// allocate.
+#[derive(Eq, PartialEq, Default, Debug)]
+pub struct Scratch {
+	params: Vec<Parameter>,
+	stack: Vec<Parameter>,
+	buffer: Vec<u8>,
+}
+
+/// Observer of the items executed during an expansion.
+///
+/// Any `FnMut(&Step)` closure is an observer.
+pub trait Observer {
+	/// Called after every executed item.
+	fn step(&mut self, step: &Step);
+}
+
+impl<F: FnMut(&Step)> Observer for F {
+	fn step(&mut self, step: &Step) {
+		self(step)
+	}
+}
+
+impl fmt::Debug for dyn Observer + '_ {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		f.write_str("Observer")
+	}
+}
+
+/// An executed item along with the state around it.
+///
+/// Items skipped by a conditional are not executed, so they're not reported.
+#[derive(Eq, PartialEq, Debug)]
+pub struct Step<'a> {
+	/// The byte offset of the item in the string.
+	pub position: usize,
+
+	/// The executed item.
+	pub item: Item<'a>,
+
+	/// The state before executing the item.
+	pub before: &'a State,
+
+	/// The state after executing the item.
+	pub after: &'a State,
+
+	/// For `%t` whether its branch was taken.
+	pub branch: Option<bool>,
+}
+
+/// A snapshot of the expansion state.
+#[derive(Eq, PartialEq, Clone, Default, Debug)]
+pub struct State {
+	/// The parameters, including any increment.
+	pub params: Vec<Parameter>,
+
+	/// The stack, the top is the last element.
+	pub stack: Vec<Parameter>,
+
+	/// The static and dynamic variables.
+	pub context: Context,
+}
+
+impl State {
+	fn capture(params: &[Parameter], stack: &[Parameter], context: &Context) -> Self {
+		State { params: params.to_vec(), stack: stack.to_vec(), context: context.clone() }
+	}
+}
+
 /// Expand a parametrized string.
 ///
 /// ## Examples
@@ -144,9 +289,11 @@ macro_rules! expand {
 	);
 
 	($value:expr => $context:expr; $($item:expr),*) => ({
+		use $crate::Expand;
 		let mut output = ::std::vec::Vec::new();
 
-		$crate::expand!(&mut output, $value => $context; $($item),*).map(|()| output)
+		$value.expand_with(&mut output, &[$($item.into()),*], $context,
+			&mut ::std::default::Default::default()).map(|()| output)
 	});
 
 	($output:expr, $value:expr) => (
@@ -175,74 +322,116 @@ impl Expand for [u8] {
 		context: &mut Context,
 	) -> error::Result<()> {
 		let mut output = BufWriter::new(output);
-		let mut input = self;
-		let mut params: [Parameter; 9] = Default::default();
-		let mut stack = Vec::new();
-		let mut conditional = false;
-		let mut incremented = false;
-
-		for (dest, source) in params.iter_mut().zip(parameters.iter()) {
-			*dest = source.clone();
-		}
+		self.expand_with(&mut output, parameters, context, &mut Scratch::default())?;
+		output.flush()?;
 
-		macro_rules! next {
-			() => {
-				match parse(input) {
-					Ok((rest, item)) => {
-						input = rest;
-						item
-					}
+		Ok(())
+	}
 
-					Err(_) => return Err(error::Expand::Invalid.into()),
-				}
-			};
-		}
+	fn expand_with<W: Write>(
+		&self,
+		output: W,
+		parameters: &[Parameter],
+		context: &mut Context,
+		scratch: &mut Scratch,
+	) -> error::Result<()> {
+		// Like ncurses, missing parameters default to `0`.
+		evaluate(self, output, parameters, 9, context, scratch, None)
+	}
 
-		'main: while !input.is_empty() {
-			match next!() {
-				Item::Conditional(Conditional::If) => {
-					conditional = true;
+	fn expand_traced<W: Write>(
+		&self,
+		output: W,
+		parameters: &[Parameter],
+		context: &mut Context,
+		scratch: &mut Scratch,
+		observer: &mut dyn Observer,
+	) -> error::Result<()> {
+		evaluate(self, output, parameters, 9, context, scratch, Some(observer))
+	}
+}
+
+/// Evaluate a parametrized string.
+///
+/// The parameters are padded with defaults up to `minimum`, referencing any
+/// parameter past those is an error.
+///
+/// When an observer is given every executed item is reported to it.
+pub(crate) fn evaluate<W: Write>(
+	input: &[u8],
+	mut output: W,
+	parameters: &[Parameter],
+	minimum: usize,
+	context: &mut Context,
+	scratch: &mut Scratch,
+	mut observer: Option<&mut dyn Observer>,
+) -> error::Result<()> {
+	let Scratch { ref mut params, ref mut stack, .. } = *scratch;
+	let string = input;
+	let mut input = input;
+	let mut conditional = false;
+	let mut incremented = false;
+
+	params.clear();
+	params.extend_from_slice(parameters);
+	params.resize(params.len().max(minimum), Default::default());
+	stack.clear();
+
+	macro_rules! next {
+		() => {
+			match parse(input) {
+				Ok((rest, item)) if rest.len() < input.len() => {
+					input = rest;
+					item
 				}
 
-				Item::Conditional(Conditional::End) if conditional => {
-					conditional = false;
+				_ => {
+					let position = string.len() - input.len();
+					return Err(failure(error::Expand::Invalid, position, None, stack));
 				}
+			}
+		};
+	}
 
-				Item::Conditional(Conditional::Then) if conditional => match stack.pop() {
-					Some(Parameter::Number(0)) => {
-						let mut level = 0;
+	while !input.is_empty() {
+		let position = string.len() - input.len();
+		let item = next!();
+		let before = observer.as_ref().map(|_| State::capture(params, stack, context));
+		let mut branch = None;
+
+		macro_rules! fail {
+			($kind:ident $(, $popped:expr)*) => ({
+				$(stack.push($popped);)*
+				return Err(failure(error::Expand::$kind, position, Some(&item), stack));
+			});
+		}
 
-						while !input.is_empty() {
-							match next!() {
-								Item::Conditional(Conditional::End)
-								| Item::Conditional(Conditional::Else)
-									if level == 0 =>
-								{
-									continue 'main
-								}
+		match item {
+			Item::Conditional(Conditional::If) => {
+				conditional = true;
+			}
 
-								Item::Conditional(Conditional::If) => level += 1,
+			Item::Conditional(Conditional::End) if conditional => {
+				conditional = false;
+			}
 
-								Item::Conditional(Conditional::End) => level -= 1,
+			Item::Conditional(Conditional::Then) if conditional => match stack.pop() {
+				Some(Parameter::Number(0)) => {
+					let mut level = 0;
+					branch = Some(false);
 
-								_ => (),
-							}
+					loop {
+						if input.is_empty() {
+							fail!(Invalid, Parameter::Number(0));
 						}
 
-						return Err(error::Expand::Invalid.into());
-					}
-
-					Some(_) => (),
-
-					None => return Err(error::Expand::StackUnderflow.into()),
-				},
-
-				Item::Conditional(Conditional::Else) if conditional => {
-					let mut level = 0;
-
-					while !input.is_empty() {
 						match next!() {
-							Item::Conditional(Conditional::End) if level == 0 => continue 'main,
+							Item::Conditional(Conditional::End)
+							| Item::Conditional(Conditional::Else)
+								if level == 0 =>
+							{
+								break
+							}
 
 							Item::Conditional(Conditional::If) => level += 1,
 
@@ -251,287 +440,344 @@ impl Expand for [u8] {
 							_ => (),
 						}
 					}
-
-					return Err(error::Expand::Invalid.into());
 				}
 
-				Item::Conditional(..) => return Err(error::Expand::Invalid.into()),
+				Some(_) => branch = Some(true),
 
-				Item::String(value) => output.write_all(value)?,
+				None => fail!(StackUnderflow),
+			},
 
-				Item::Constant(Constant::Character(ch)) => {
-					stack.push(Parameter::Number(ch as i32));
-				}
-
-				Item::Constant(Constant::Integer(value)) => {
-					stack.push(Parameter::Number(value));
-				}
+			Item::Conditional(Conditional::Else) if conditional => {
+				let mut level = 0;
 
-				Item::Variable(Variable::Length) => match stack.pop() {
-					Some(Parameter::String(ref value)) => {
-						stack.push(Parameter::Number(value.len() as i32));
+				loop {
+					if input.is_empty() {
+						fail!(Invalid);
 					}
 
-					Some(_) => {
-						return Err(error::Expand::TypeMismatch.into());
-					}
+					match next!() {
+						Item::Conditional(Conditional::End) if level == 0 => break,
+
+						Item::Conditional(Conditional::If) => level += 1,
+
+						Item::Conditional(Conditional::End) => level -= 1,
 
-					None => {
-						return Err(error::Expand::StackUnderflow.into());
+						_ => (),
 					}
-				},
+				}
+			}
+
+			Item::Conditional(..) => fail!(Invalid),
+
+			Item::String(value) => output.write_all(value)?,
+
+			Item::Constant(Constant::Character(ch)) => {
+				stack.push(Parameter::Number(ch as i32));
+			}
+
+			Item::Constant(Constant::Integer(value)) => {
+				stack.push(Parameter::Number(value));
+			}
 
-				Item::Variable(Variable::Push(index)) => {
-					stack.push(params[index as usize].clone());
+			Item::Variable(Variable::Length) => match stack.pop() {
+				Some(Parameter::String(ref value)) => {
+					stack.push(Parameter::Number(value.len() as i32));
 				}
 
-				Item::Variable(Variable::Set(dynamic, index)) => {
-					if let Some(value) = stack.pop() {
-						if dynamic {
-							context.dynamic[index as usize] = value.clone();
-						} else {
-							context.fixed[index as usize] = value.clone();
-						}
-					} else {
-						return Err(error::Expand::StackUnderflow.into());
-					}
+				Some(value) => fail!(TypeMismatch, value),
+
+				None => fail!(StackUnderflow),
+			},
+
+			Item::Variable(Variable::Push(index)) => {
+				if let Some(value) = params.get(index as usize) {
+					stack.push(value.clone());
+				} else {
+					fail!(MissingParameter);
 				}
+			}
 
-				Item::Variable(Variable::Get(dynamic, index)) => {
+			Item::Variable(Variable::Set(dynamic, index)) => {
+				if let Some(value) = stack.pop() {
 					if dynamic {
-						stack.push(context.dynamic[index as usize].clone());
+						context.dynamic[index as usize] = value.clone();
 					} else {
-						stack.push(context.fixed[index as usize].clone());
+						context.fixed[index as usize] = value.clone();
 					}
+				} else {
+					fail!(StackUnderflow);
+				}
+			}
+
+			Item::Variable(Variable::Get(dynamic, index)) => {
+				if dynamic {
+					stack.push(context.dynamic[index as usize].clone());
+				} else {
+					stack.push(context.fixed[index as usize].clone());
 				}
+			}
 
-				Item::Operation(Operation::Increment) if !incremented => {
-					incremented = true;
+			Item::Operation(Operation::Increment) if !incremented => {
+				incremented = true;
 
-					if let (&Parameter::Number(x), &Parameter::Number(y)) = (&params[0], &params[1])
-					{
-						params[0] = Parameter::Number(x + 1);
-						params[1] = Parameter::Number(y + 1);
+				for param in params.iter_mut().take(2) {
+					if let Parameter::Number(ref mut value) = *param {
+						*value += 1;
 					} else {
-						return Err(error::Expand::TypeMismatch.into());
+						fail!(TypeMismatch);
 					}
 				}
+			}
 
-				Item::Operation(Operation::Increment) => (),
-
-				Item::Operation(Operation::Binary(operation)) => match (stack.pop(), stack.pop()) {
-					(Some(Parameter::Number(y)), Some(Parameter::Number(x))) => {
-						stack.push(Parameter::Number(match operation {
-							Binary::Add => x + y,
-							Binary::Subtract => x - y,
-							Binary::Multiply => x * y,
-							Binary::Divide => {
-								if y != 0 {
-									x / y
-								} else {
-									0
-								}
+			Item::Operation(Operation::Increment) => (),
+
+			Item::Operation(Operation::Binary(operation)) => match (stack.pop(), stack.pop()) {
+				(Some(Parameter::Number(y)), Some(Parameter::Number(x))) => {
+					stack.push(Parameter::Number(match operation {
+						Binary::Add => x + y,
+						Binary::Subtract => x - y,
+						Binary::Multiply => x * y,
+						Binary::Divide => {
+							if y != 0 {
+								x / y
+							} else {
+								0
 							}
-							Binary::Remainder => {
-								if y != 0 {
-									x % y
-								} else {
-									0
-								}
+						}
+						Binary::Remainder => {
+							if y != 0 {
+								x % y
+							} else {
+								0
 							}
+						}
 
-							Binary::AND => x & y,
-							Binary::OR => x | y,
-							Binary::XOR => x ^ y,
+						Binary::AND => x & y,
+						Binary::OR => x | y,
+						Binary::XOR => x ^ y,
 
-							Binary::And => (x != 0 && y != 0) as i32,
-							Binary::Or => (x != 0 || y != 0) as i32,
+						Binary::And => (x != 0 && y != 0) as i32,
+						Binary::Or => (x != 0 || y != 0) as i32,
 
-							Binary::Equal => (x == y) as i32,
-							Binary::Greater => (x > y) as i32,
-							Binary::Lesser => (x < y) as i32,
-						}))
-					}
+						Binary::Equal => (x == y) as i32,
+						Binary::Greater => (x > y) as i32,
+						Binary::Lesser => (x < y) as i32,
+					}))
+				}
 
-					(Some(_), Some(_)) => return Err(error::Expand::TypeMismatch.into()),
+				(Some(y), Some(x)) => fail!(TypeMismatch, x, y),
 
-					_ => return Err(error::Expand::StackUnderflow.into()),
-				},
+				(Some(y), None) => fail!(StackUnderflow, y),
 
-				Item::Operation(Operation::Unary(operation)) => match stack.pop() {
-					Some(Parameter::Number(x)) => stack.push(Parameter::Number(match operation {
-						Unary::Not => (x != 0) as i32,
-						Unary::NOT => !x,
-					})),
+				(None, _) => fail!(StackUnderflow),
+			},
 
-					Some(_) => return Err(error::Expand::TypeMismatch.into()),
+			Item::Operation(Operation::Unary(operation)) => match stack.pop() {
+				Some(Parameter::Number(x)) => stack.push(Parameter::Number(match operation {
+					Unary::Not => (x != 0) as i32,
+					Unary::NOT => !x,
+				})),
 
-					_ => return Err(error::Expand::StackUnderflow.into()),
-				},
+				Some(value) => fail!(TypeMismatch, value),
 
-				Item::Print(p) => {
-					/// Calculate the length of a formatted number.
-					fn length(value: i32, p: &Print) -> usize {
-						let digits = match p.format {
-							Format::Dec => (value as f32).abs().log(10.0).floor() as usize + 1,
+				None => fail!(StackUnderflow),
+			},
 
-							Format::Oct => (value as f32).abs().log(8.0).floor() as usize + 1,
+			Item::Print(p) => {
+				/// Calculate the length of a formatted number.
+				fn length(value: i32, p: &Print) -> usize {
+					let digits = match p.format {
+						Format::Dec => (value as f32).abs().log(10.0).floor() as usize + 1,
 
-							Format::Hex | Format::HEX => {
-								(value as f32).abs().log(16.0).floor() as usize + 1
-							}
+						Format::Oct => (value as f32).abs().log(8.0).floor() as usize + 1,
 
-							_ => unreachable!(),
-						};
+						Format::Hex | Format::HEX => {
+							(value as f32).abs().log(16.0).floor() as usize + 1
+						}
 
-						let mut length = digits;
+						_ => unreachable!(),
+					};
 
-						// Add the minimum number of digits.
-						if p.flags.precision > digits {
-							length += p.flags.precision - digits;
-						}
+					let mut length = digits;
 
-						// Add the sign if present.
-						if p.format == Format::Dec && (value < 0 || p.flags.sign) {
-							length += 1;
-						}
+					// Add the minimum number of digits.
+					if p.flags.precision > digits {
+						length += p.flags.precision - digits;
+					}
 
-						// Add the alternate representation.
-						if p.flags.alternate {
-							match p.format {
-								Format::Hex | Format::HEX => length += 2,
+					// Add the sign if present.
+					if p.format == Format::Dec && (value < 0 || p.flags.sign) {
+						length += 1;
+					}
 
-								Format::Oct => length += 1,
+					// Add the alternate representation.
+					if p.flags.alternate {
+						match p.format {
+							Format::Hex | Format::HEX => length += 2,
 
-								_ => (),
-							}
-						}
+							Format::Oct => length += 1,
 
-						length
+							_ => (),
+						}
 					}
 
-					macro_rules! w {
-						($value:expr) => (
-							output.write_all($value)?
-						);
+					length
+				}
 
-						($($item:tt)*) => (
-							write!(output, $($item)*)?
-						);
-					}
+				macro_rules! w {
+					($value:expr) => (
+						output.write_all($value)?
+					);
 
-					macro_rules! f {
-						(by $length:expr) => (
-							for _ in 0 .. p.flags.width - $length {
-								output.write_all(if p.flags.space { b" " } else { b"0" })?;
-							}
-						);
+					($($item:tt)*) => (
+						write!(output, $($item)*)?
+					);
+				}
 
-						(before by $length:expr) => (
-							if !p.flags.left && p.flags.width > $length {
-								f!(by $length);
-							}
-						);
+				macro_rules! f {
+					(by $length:expr) => (
+						for _ in 0 .. p.flags.width - $length {
+							output.write_all(if p.flags.space { b" " } else { b"0" })?;
+						}
+					);
 
-						(after by $length:expr) => (
-							if p.flags.left && p.flags.width > $length {
-								f!(by $length);
-							}
-						);
+					(before by $length:expr) => (
+						if !p.flags.left && p.flags.width > $length {
+							f!(by $length);
+						}
+					);
 
-						(before $value:expr) => (
-							f!(before by length($value, &p));
-						);
+					(after by $length:expr) => (
+						if p.flags.left && p.flags.width > $length {
+							f!(by $length);
+						}
+					);
 
-						(after $value:expr) => (
-							f!(after by length($value, &p));
-						);
-					}
+					(before $value:expr) => (
+						f!(before by length($value, &p));
+					);
 
-					match (p.format, stack.pop()) {
-						(Format::Str, Some(Parameter::String(ref value))) => {
-							let mut value = &value[..];
+					(after $value:expr) => (
+						f!(after by length($value, &p));
+					);
+				}
 
-							if p.flags.precision > 0 && p.flags.precision < value.len() {
-								value = &value[..p.flags.precision];
-							}
+				match (p.format, stack.pop()) {
+					(Format::Str, Some(Parameter::String(ref value))) => {
+						let mut value = &value[..];
 
-							f!(before by value.len());
-							w!(value);
-							f!(after by value.len());
+						if p.flags.precision > 0 && p.flags.precision < value.len() {
+							value = &value[..p.flags.precision];
 						}
 
-						(Format::Chr, Some(Parameter::Number(value))) => {
-							w!("{}", value as u8 as char)
-						}
+						f!(before by value.len());
+						w!(value);
+						f!(after by value.len());
+					}
 
-						(Format::Uni, Some(Parameter::Number(value))) => w!(
-							"{}",
-							char::from_u32(value as u32).ok_or(error::Expand::TypeMismatch)?
-						),
+					(Format::Chr, Some(Parameter::Number(value))) => {
+						w!("{}", value as u8 as char)
+					}
 
-						(Format::Dec, Some(Parameter::Number(value))) => {
-							f!(before value);
+					(Format::Uni, Some(Parameter::Number(value))) => {
+						match char::from_u32(value as u32) {
+							Some(ch) => w!("{}", ch),
 
-							if p.flags.sign && value >= 0 {
-								w!(b"+");
-							}
+							None => fail!(TypeMismatch, Parameter::Number(value)),
+						}
+					}
 
-							w!("{:.1$}", value, p.flags.precision);
+					(Format::Dec, Some(Parameter::Number(value))) => {
+						f!(before value);
 
-							f!(after value);
+						if p.flags.sign && value >= 0 {
+							w!(b"+");
 						}
 
-						(Format::Oct, Some(Parameter::Number(value))) => {
-							f!(before value);
+						w!("{:.1$}", value, p.flags.precision);
 
-							if p.flags.alternate {
-								w!(b"0");
-							}
+						f!(after value);
+					}
 
-							w!("{:.1$o}", value, p.flags.precision);
+					(Format::Oct, Some(Parameter::Number(value))) => {
+						f!(before value);
 
-							f!(after value);
+						if p.flags.alternate {
+							w!(b"0");
 						}
 
-						(Format::Hex, Some(Parameter::Number(value))) => {
-							f!(before value);
+						w!("{:.1$o}", value, p.flags.precision);
 
-							if p.flags.alternate {
-								w!(b"0x");
-							}
+						f!(after value);
+					}
 
-							w!("{:.1$x}", value, p.flags.precision);
+					(Format::Hex, Some(Parameter::Number(value))) => {
+						f!(before value);
 
-							f!(after value);
+						if p.flags.alternate {
+							w!(b"0x");
 						}
 
-						(Format::HEX, Some(Parameter::Number(value))) => {
-							f!(before value);
+						w!("{:.1$x}", value, p.flags.precision);
 
-							if p.flags.alternate {
-								w!(b"0X");
-							}
+						f!(after value);
+					}
 
-							w!("{:.1$X}", value, p.flags.precision);
+					(Format::HEX, Some(Parameter::Number(value))) => {
+						f!(before value);
 
-							f!(after value);
+						if p.flags.alternate {
+							w!(b"0X");
 						}
 
-						(_, Some(_)) => return Err(error::Expand::TypeMismatch.into()),
+						w!("{:.1$X}", value, p.flags.precision);
 
-						(_, None) => return Err(error::Expand::StackUnderflow.into()),
+						f!(after value);
 					}
+
+					(_, Some(value)) => fail!(TypeMismatch, value),
+
+					(_, None) => fail!(StackUnderflow),
 				}
 			}
 		}
 
-		Ok(())
+		if let (Some(observer), Some(before)) = (observer.as_mut(), before) {
+			observer.step(&Step {
+				position,
+				item,
+				before: &before,
+				after: &State::capture(params, stack, context),
+				branch,
+			});
+		}
+	}
+
+	Ok(())
+}
+
+/// Build an expansion error for the given item.
+pub(crate) fn failure(
+	kind: error::Expand,
+	position: usize,
+	item: Option<&Item>,
+	stack: &[Parameter],
+) -> error::Error {
+	error::Expansion {
+		kind,
+		capability: None,
+		position: Some(position),
+		item: item
+			.map(|item| String::from_utf8_lossy(&disassemble::assemble(&[*item])).into_owned()),
+		stack: stack.to_vec(),
 	}
+	.into()
 }
 
 #[cfg(test)]
 mod test {
+	use super::*;
+
 	#[test]
 	fn test_basic_setabf() {
 		assert_eq!(b"\\E[48;5;1m".to_vec(), expand!(b"\\E[48;5;%p1%dm"; 1).unwrap());
@@ -552,4 +798,109 @@ mod test {
 
 		assert_eq!(b"3".to_vec(), expand!(b"%?%p1%t%e%p2%t2%e%p3%t3%;"; 0, 0, 1).unwrap());
 	}
+
+	#[test]
+	fn missing() {
+		let mut output = Vec::new();
+		let mut scratch = Scratch::default();
+
+		evaluate(b"%p1%d", &mut output, &[], 0, &mut Default::default(), &mut scratch, None)
+			.unwrap_err();
+
+		evaluate(
+			b"%i%p1%d",
+			&mut output,
+			&[1.into()],
+			0,
+			&mut Default::default(),
+			&mut scratch,
+			None,
+		)
+		.unwrap();
+
+		assert_eq!(b"2".to_vec(), output);
+		assert_eq!(b"0".to_vec(), expand!(b"%p9%d").unwrap());
+	}
+
+	#[test]
+	fn errors() {
+		fn details(result: error::Result<Vec<u8>>) -> error::Expansion {
+			match result {
+				Err(error::Error::Expand(details)) => details,
+				_ => panic!("expected an expansion error"),
+			}
+		}
+
+		let error = details(expand!(b"\x1B[%p1%p2%+%dm"; 1, "a"));
+		assert_eq!(error::Expand::TypeMismatch, error.kind);
+		assert_eq!(Some(8), error.position);
+		assert_eq!(Some("%+".into()), error.item);
+		assert_eq!(vec![Parameter::from(1), Parameter::from("a")], error.stack);
+		assert_eq!(
+			"Type mismatch at byte 8 (`%+`) with stack [1, \"a\"].",
+			error::Error::from(error).to_string()
+		);
+
+		let error = details(expand!(b"%p1%d%d"; 1));
+		assert_eq!(error::Expand::StackUnderflow, error.kind);
+		assert_eq!(Some(5), error.position);
+
+		let error = details(expand!(b"foo%z"));
+		assert_eq!(error::Expand::Invalid, error.kind);
+		assert_eq!(Some(3), error.position);
+		assert_eq!(None, error.item);
+	}
+
+	#[test]
+	fn fmt() {
+		let mut output = String::new();
+		let mut scratch = Scratch::default();
+
+		b"%p1%c"
+			.expand_fmt(&mut output, &[65.into()], &mut Default::default(), &mut scratch)
+			.unwrap();
+		assert_eq!("A", output);
+
+		assert!(b"%p1%s"
+			.expand_fmt(&mut output, &[b"\xFF"[..].into()], &mut Default::default(), &mut scratch)
+			.is_err());
+		assert_eq!("A", output);
+	}
+
+	#[test]
+	fn trace() {
+		let mut output = Vec::new();
+		let mut steps = Vec::new();
+
+		b"%?%p1%t%p1%d%e0%;"
+			.expand_traced(
+				&mut output,
+				&[7.into()],
+				&mut Default::default(),
+				&mut Scratch::default(),
+				&mut |step: &Step| {
+					steps.push(format!(
+						"{} {} {:?} {:?}",
+						step.position,
+						String::from_utf8_lossy(&disassemble::assemble(&[step.item])),
+						step.after.stack,
+						step.branch
+					))
+				},
+			)
+			.unwrap();
+
+		assert_eq!(b"7".to_vec(), output);
+		assert_eq!(
+			vec![
+				"0 %? [] None",
+				"2 %p1 [Number(7)] None",
+				"5 %t [] Some(true)",
+				"7 %p1 [Number(7)] None",
+				"10 %d [] None",
+				"12 %e [] None",
+			],
+			steps
+		);
+	}
 }
@@ -0,0 +1,217 @@
+//            DO WHAT THE FUCK YOU WANT TO PUBLIC LICENSE
+//                    Version 2, December 2004
+//
+// Copyleft (ↄ) meh. <meh@schizofreni.co> | http://meh.schizofreni.co
+//
+// Everyone is permitted to copy and distribute verbatim or modified
+// copies of this license document, and changing it is allowed as long
+// as the name is changed.
+//
+//            DO WHAT THE FUCK YOU WANT TO PUBLIC LICENSE
+//   TERMS AND CONDITIONS FOR COPYING, DISTRIBUTION AND MODIFICATION
+//
+//  0. You just DO WHAT THE FUCK YOU WANT TO.
+
+//! Guards restoring terminal modes when dropped.
+//!
+//! ## Example
+//!
+//! ```
+//! use std::io::Write;
+//! use terminfo::Database;
+//! use terminfo::guard::Guard;
+//!
+//! let info       = Database::from_path("tests/xterm-256color").unwrap();
+//! let mut output = Vec::new();
+//!
+//! {
+//!     let mut keypad = Guard::keypad(&info, &mut output).unwrap();
+//!     keypad.write_all(b"hello").unwrap();
+//! }
+//!
+//! assert_eq!(b"\x1B[?1h\x1B=hello\x1B[?1l\x1B>".to_vec(), output);
+//! ```
+
+use std::io::{self, Write};
+
+use crate::capability as cap;
+use crate::error;
+use crate::Database;
+
+/// Writes a sequence to enter a mode on creation, and the sequence to leave
+/// it when dropped, including during a panic.
+///
+/// Guards forward writes to the wrapped output, so they can be nested. When
+/// the terminal does not support the mode nothing is written.
+#[derive(Debug)]
+pub struct Guard<W: Write> {
+	output: W,
+	exit: Option<Vec<u8>>,
+}
+
+impl<W: Write> Guard<W> {
+	/// Enter a mode with the given sequences.
+	pub fn new(mut output: W, enter: &[u8], exit: Vec<u8>) -> error::Result<Self> {
+		output.write_all(enter)?;
+		output.flush()?;
+
+		Ok(Guard { output, exit: Some(exit) })
+	}
+
+	/// A guard that writes nothing.
+	fn inert(output: W) -> Self {
+		Guard { output, exit: None }
+	}
+
+	/// Enter a mode with the given pair of expanded capabilities, if both are
+	/// present.
+	fn pair(
+		output: W,
+		enter: Option<error::Result<Vec<u8>>>,
+		exit: Option<error::Result<Vec<u8>>>,
+	) -> error::Result<Self> {
+		match (enter, exit) {
+			(Some(enter), Some(exit)) => Guard::new(output, &enter?, exit?),
+			_ => Ok(Guard::inert(output)),
+		}
+	}
+
+	/// Enable bracketed paste with `BE` and `BD`.
+	pub fn bracketed_paste(info: &Database, output: W) -> error::Result<Self> {
+		Guard::pair(
+			output,
+			info.get::<cap::EnableBracketedPaste>().map(|cap| cap.expand().to_vec()),
+			info.get::<cap::DisableBracketedPaste>().map(|cap| cap.expand().to_vec()),
+		)
+	}
+
+	/// Enable focus reporting with `fe` and `fd`.
+	pub fn focus_events(info: &Database, output: W) -> error::Result<Self> {
+		Guard::pair(
+			output,
+			info.get::<cap::EnableFocusEvents>().map(|cap| cap.expand().to_vec()),
+			info.get::<cap::DisableFocusEvents>().map(|cap| cap.expand().to_vec()),
+		)
+	}
+
+	/// Begin a synchronized update with `Sync`, ending it when dropped.
+	pub fn synchronized_output(info: &Database, output: W) -> error::Result<Self> {
+		let cap = info.get::<cap::SynchronizedOutput>();
+
+		Guard::pair(
+			output,
+			cap.as_ref().map(|cap| cap.expand().mode(1).to_vec()),
+			cap.as_ref().map(|cap| cap.expand().mode(2).to_vec()),
+		)
+	}
+
+	/// Enable keypad transmit mode with `keypad_xmit` and `keypad_local`.
+	pub fn keypad(info: &Database, output: W) -> error::Result<Self> {
+		Guard::pair(
+			output,
+			info.get::<cap::KeypadXmit>().map(|cap| cap.expand().to_vec()),
+			info.get::<cap::KeypadLocal>().map(|cap| cap.expand().to_vec()),
+		)
+	}
+
+	/// Switch to the alternate screen with `enter_ca_mode` and `exit_ca_mode`.
+	pub fn alternate_screen(info: &Database, output: W) -> error::Result<Self> {
+		Guard::pair(
+			output,
+			info.get::<cap::EnterCaMode>().map(|cap| cap.expand().to_vec()),
+			info.get::<cap::ExitCaMode>().map(|cap| cap.expand().to_vec()),
+		)
+	}
+
+	/// Whether the mode was entered.
+	pub fn is_active(&self) -> bool {
+		self.exit.is_some()
+	}
+
+	/// Get a reference to the wrapped output.
+	pub fn get_ref(&self) -> &W {
+		&self.output
+	}
+
+	/// Get a mutable reference to the wrapped output.
+	pub fn get_mut(&mut self) -> &mut W {
+		&mut self.output
+	}
+
+	/// Leave the mode, reporting any error writing the sequence.
+	pub fn finish(mut self) -> error::Result<()> {
+		if let Some(exit) = self.exit.take() {
+			self.output.write_all(&exit)?;
+			self.output.flush()?;
+		}
+
+		Ok(())
+	}
+}
+
+impl<W: Write> Write for Guard<W> {
+	fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+		self.output.write(buf)
+	}
+
+	fn flush(&mut self) -> io::Result<()> {
+		self.output.flush()
+	}
+}
+
+impl<W: Write> Drop for Guard<W> {
+	fn drop(&mut self) {
+		if let Some(exit) = self.exit.take() {
+			let _ = self.output.write_all(&exit);
+			let _ = self.output.flush();
+		}
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+
+	#[test]
+	fn nested() {
+		let mut info = Database::new();
+		info.name("test")
+			.raw("BE", "\x1B[?2004h")
+			.raw("BD", "\x1B[?2004l")
+			.raw("Sync", "\x1B[?2026%?%p1%{1}%-%tl%eh%;")
+			.raw("smcup", "\x1B[?1049h")
+			.raw("rmcup", "\x1B[?1049l");
+		let info = info.build().unwrap();
+		let mut output = Vec::new();
+
+		{
+			let screen = Guard::alternate_screen(&info, &mut output).unwrap();
+			let paste = Guard::bracketed_paste(&info, screen).unwrap();
+			let focus = Guard::focus_events(&info, paste).unwrap();
+			let mut sync = Guard::synchronized_output(&info, focus).unwrap();
+
+			assert!(sync.is_active());
+			assert!(!sync.get_ref().is_active());
+			sync.write_all(b"x").unwrap();
+		}
+
+		assert_eq!(
+			b"\x1B[?1049h\x1B[?2004h\x1B[?2026hx\x1B[?2026l\x1B[?2004l\x1B[?1049l".to_vec(),
+			output
+		);
+	}
+
+	#[test]
+	fn unwind() {
+		let info = Database::from_path("tests/xterm-256color").unwrap();
+		let mut output = Vec::new();
+
+		let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+			let _keypad = Guard::keypad(&info, &mut output).unwrap();
+			panic!("oops");
+		}));
+
+		assert!(result.is_err());
+		assert_eq!(b"\x1B[?1h\x1B=\x1B[?1l\x1B>".to_vec(), output);
+	}
+}
@@ -0,0 +1,834 @@
+//            DO WHAT THE FUCK YOU WANT TO PUBLIC LICENSE
+//                    Version 2, December 2004
+//
+// Copyleft (ↄ) meh. <meh@schizofreni.co> | http://meh.schizofreni.co
+//
+// Everyone is permitted to copy and distribute verbatim or modified
+// copies of this license document, and changing it is allowed as long
+// as the name is changed.
+//
+//            DO WHAT THE FUCK YOU WANT TO PUBLIC LICENSE
+//   TERMS AND CONDITIONS FOR COPYING, DISTRIBUTION AND MODIFICATION
+//
+//  0. You just DO WHAT THE FUCK YOU WANT TO.
+
+//! Decoding of input sequences into keys.
+//!
+//! ## Example
+//!
+//! ```
+//! use terminfo::Database;
+//! use terminfo::input::{Event, Key, KeyCode, KeyDecoder, Modifiers};
+//!
+//! let info = Database::from_path("tests/xterm-256color").unwrap();
+//! let mut decoder = KeyDecoder::new(&info);
+//!
+//! decoder.feed(b"\x1BOAa\x1B");
+//! assert_eq!(Some(Event::Key(KeyCode::Up.into())), decoder.decode());
+//! assert_eq!(Some(Event::Key(KeyCode::Char('a').into())), decoder.decode());
+//!
+//! // A lone escape could be the start of a sequence.
+//! assert_eq!(None, decoder.decode());
+//! assert_eq!(Some(Event::Key(KeyCode::Escape.into())), decoder.flush());
+//! ```
+
+use std::collections::BTreeMap;
+use std::ops::{BitOr, BitOrAssign};
+use std::str;
+
+use crate::capability::Value;
+use crate::mouse::{Decoded, Mouse, MouseEvent};
+use crate::names;
+use crate::Database;
+
+/// Keyboard modifiers.
+#[derive(Eq, PartialEq, Copy, Clone, Hash, Default, Debug)]
+pub struct Modifiers(u8);
+
+impl Modifiers {
+	/// No modifiers.
+	pub const NONE: Modifiers = Modifiers(0);
+
+	/// The shift key.
+	pub const SHIFT: Modifiers = Modifiers(1);
+
+	/// The alt key.
+	pub const ALT: Modifiers = Modifiers(2);
+
+	/// The control key.
+	pub const CTRL: Modifiers = Modifiers(4);
+
+	/// The meta key.
+	pub const META: Modifiers = Modifiers(8);
+
+	/// Create the modifiers from the parameter used by xterm, `1` plus the
+	/// modifier bits.
+	pub fn from_xterm(value: u8) -> Self {
+		Modifiers(value.saturating_sub(1) & 0xF)
+	}
+
+	/// The parameter used by xterm for these modifiers.
+	pub fn to_xterm(self) -> u8 {
+		self.0 + 1
+	}
+
+	/// Whether all the given modifiers are present.
+	pub fn contains(self, other: Modifiers) -> bool {
+		self.0 & other.0 == other.0
+	}
+
+	/// Whether no modifiers are present.
+	pub fn is_empty(self) -> bool {
+		self.0 == 0
+	}
+}
+
+impl BitOr for Modifiers {
+	type Output = Self;
+
+	fn bitor(self, other: Self) -> Self {
+		Modifiers(self.0 | other.0)
+	}
+}
+
+impl BitOrAssign for Modifiers {
+	fn bitor_assign(&mut self, other: Self) {
+		self.0 |= other.0;
+	}
+}
+
+/// A key independent of modifiers.
+#[derive(Eq, PartialEq, Copy, Clone, Hash, Debug)]
+pub enum KeyCode {
+	/// A character.
+	Char(char),
+
+	/// A function key.
+	F(u8),
+
+	Backspace,
+	Enter,
+	Tab,
+	Escape,
+
+	Up,
+	Down,
+	Left,
+	Right,
+
+	Home,
+	End,
+	PageUp,
+	PageDown,
+	Insert,
+	Delete,
+	Begin,
+
+	/// The lower left key, `key_ll`.
+	HomeDown,
+
+	/// The upper left key of the keypad.
+	KeypadUpperLeft,
+
+	/// The upper right key of the keypad.
+	KeypadUpperRight,
+
+	/// The center key of the keypad.
+	KeypadCenter,
+
+	/// The lower left key of the keypad.
+	KeypadLowerLeft,
+
+	/// The lower right key of the keypad.
+	KeypadLowerRight,
+
+	InsertLine,
+	DeleteLine,
+	ExitInsert,
+	ClearToEndOfLine,
+	ClearToEndOfScreen,
+	ScrollForward,
+	ScrollBackward,
+	SetTab,
+	ClearTab,
+	ClearAllTabs,
+
+	Clear,
+	Cancel,
+	Close,
+	Command,
+	Copy,
+	Create,
+	Exit,
+	Find,
+	Help,
+	Mark,
+	Message,
+	Move,
+	Next,
+	Open,
+	Options,
+	Previous,
+	Print,
+	Redo,
+	Reference,
+	Refresh,
+	Replace,
+	Restart,
+	Resume,
+	Save,
+	Select,
+	Suspend,
+	Undo,
+}
+
+/// A key with its modifiers.
+#[derive(Eq, PartialEq, Copy, Clone, Hash, Debug)]
+pub struct Key {
+	/// The key.
+	pub code: KeyCode,
+
+	/// The modifiers held.
+	pub modifiers: Modifiers,
+}
+
+impl Key {
+	/// Create a key with the given modifiers.
+	pub fn new(code: KeyCode, modifiers: Modifiers) -> Self {
+		Key { code, modifiers }
+	}
+
+	/// The key sent by the given capability, either by its short or long name.
+	///
+	/// Function keys past `F12` are the modified variants of `F1` to `F12`, in
+	/// order `Shift`, `Ctrl`, `Ctrl+Shift`, `Alt` and `Alt+Shift`. Extended
+	/// capabilities like `kRIT5` carry the modifiers as their xterm parameter.
+	///
+	/// ## Example
+	///
+	/// ```
+	/// use terminfo::input::{Key, KeyCode, Modifiers};
+	///
+	/// assert_eq!(Some(Key::new(KeyCode::Right, Modifiers::CTRL)), Key::from_capability("kRIT5"));
+	/// assert_eq!(Some(Key::new(KeyCode::F(2), Modifiers::SHIFT)), Key::from_capability("kf14"));
+	/// ```
+	pub fn from_capability(name: &str) -> Option<Self> {
+		let name = names::ALIASES.get(name).copied().unwrap_or(name);
+
+		if let Some(&(_, code, modifiers)) = KEYS.iter().find(|&&(key, ..)| key == name) {
+			return Some(Key::new(code, modifiers));
+		}
+
+		if let Some(number) = name.strip_prefix("key_f") {
+			let number = number.parse::<u8>().ok()?;
+
+			if number <= 12 {
+				return Some(KeyCode::F(number).into());
+			}
+
+			let modifiers = match (number - 1) / 12 {
+				1 => Modifiers::SHIFT,
+				2 => Modifiers::CTRL,
+				3 => Modifiers::CTRL | Modifiers::SHIFT,
+				4 => Modifiers::ALT,
+				5 => Modifiers::ALT | Modifiers::SHIFT,
+				_ => return None,
+			};
+
+			return Some(Key::new(KeyCode::F((number - 1) % 12 + 1), modifiers));
+		}
+
+		let (prefix, suffix) =
+			name.split_at(name.trim_end_matches(|c: char| c.is_ascii_digit()).len());
+		let &(_, code) = EXTENDED.iter().find(|&&(key, _)| key == prefix)?;
+
+		// Unsuffixed names are the shifted keys.
+		let modifiers = match suffix {
+			"" => Modifiers::SHIFT,
+			suffix => Modifiers::from_xterm(suffix.parse().ok().filter(|&value| value > 1)?),
+		};
+
+		Some(Key::new(code, modifiers))
+	}
+
+	/// The sequence the given terminal sends for the key.
+	///
+	/// When the terminal doesn't define a modified key, it's derived from the
+	/// unmodified one following the xterm convention, `\E[1;5C` for
+	/// `Ctrl+Right` when `Right` is `\EOC` or `\E[C`, and `\E[3;5~` for
+	/// `Ctrl+Delete` when `Delete` is `\E[3~`.
+	///
+	/// ## Example
+	///
+	/// ```
+	/// use terminfo::Database;
+	/// use terminfo::input::{Key, KeyCode, Modifiers};
+	///
+	/// let info = Database::from_path("tests/xterm-256color").unwrap();
+	/// let key  = Key::new(KeyCode::Right, Modifiers::CTRL | Modifiers::ALT);
+	///
+	/// assert_eq!(Some(b"\x1B[1;7C".to_vec()), key.sequence(&info));
+	/// ```
+	pub fn sequence(&self, info: &Database) -> Option<Vec<u8>> {
+		let keys = keys(info);
+		let find = |key: Key| {
+			keys.iter().find(|&&(_, _, other)| other == key).map(|&(_, sequence, _)| sequence)
+		};
+
+		if let Some(sequence) = find(*self) {
+			return Some(sequence.to_vec());
+		}
+
+		if self.modifiers.is_empty() {
+			return None;
+		}
+
+		modified(find(self.code.into())?, self.modifiers)
+	}
+}
+
+impl From<KeyCode> for Key {
+	fn from(code: KeyCode) -> Self {
+		Key::new(code, Modifiers::NONE)
+	}
+}
+
+/// A decoded input event.
+#[derive(Eq, PartialEq, Clone, Debug)]
+pub enum Event {
+	/// A key press.
+	Key(Key),
+
+	/// A mouse report.
+	Mouse(MouseEvent),
+
+	/// An unknown sequence.
+	Raw(Vec<u8>),
+}
+
+/// Standard key capabilities.
+const KEYS: &[(&str, KeyCode, Modifiers)] = &[
+	("key_backspace", KeyCode::Backspace, Modifiers::NONE),
+	("key_enter", KeyCode::Enter, Modifiers::NONE),
+	("key_btab", KeyCode::Tab, Modifiers::SHIFT),
+	("key_up", KeyCode::Up, Modifiers::NONE),
+	("key_down", KeyCode::Down, Modifiers::NONE),
+	("key_left", KeyCode::Left, Modifiers::NONE),
+	("key_right", KeyCode::Right, Modifiers::NONE),
+	("key_home", KeyCode::Home, Modifiers::NONE),
+	("key_end", KeyCode::End, Modifiers::NONE),
+	("key_ppage", KeyCode::PageUp, Modifiers::NONE),
+	("key_npage", KeyCode::PageDown, Modifiers::NONE),
+	("key_ic", KeyCode::Insert, Modifiers::NONE),
+	("key_dc", KeyCode::Delete, Modifiers::NONE),
+	("key_beg", KeyCode::Begin, Modifiers::NONE),
+	("key_ll", KeyCode::HomeDown, Modifiers::NONE),
+	("key_a1", KeyCode::KeypadUpperLeft, Modifiers::NONE),
+	("key_a3", KeyCode::KeypadUpperRight, Modifiers::NONE),
+	("key_b2", KeyCode::KeypadCenter, Modifiers::NONE),
+	("key_c1", KeyCode::KeypadLowerLeft, Modifiers::NONE),
+	("key_c3", KeyCode::KeypadLowerRight, Modifiers::NONE),
+	("key_il", KeyCode::InsertLine, Modifiers::NONE),
+	("key_dl", KeyCode::DeleteLine, Modifiers::NONE),
+	("key_eic", KeyCode::ExitInsert, Modifiers::NONE),
+	("key_eol", KeyCode::ClearToEndOfLine, Modifiers::NONE),
+	("key_eos", KeyCode::ClearToEndOfScreen, Modifiers::NONE),
+	("key_sf", KeyCode::ScrollForward, Modifiers::NONE),
+	("key_sr", KeyCode::ScrollBackward, Modifiers::NONE),
+	("key_stab", KeyCode::SetTab, Modifiers::NONE),
+	("key_ctab", KeyCode::ClearTab, Modifiers::NONE),
+	("key_catab", KeyCode::ClearAllTabs, Modifiers::NONE),
+	("key_clear", KeyCode::Clear, Modifiers::NONE),
+	("key_cancel", KeyCode::Cancel, Modifiers::NONE),
+	("key_close", KeyCode::Close, Modifiers::NONE),
+	("key_command", KeyCode::Command, Modifiers::NONE),
+	("key_copy", KeyCode::Copy, Modifiers::NONE),
+	("key_create", KeyCode::Create, Modifiers::NONE),
+	("key_exit", KeyCode::Exit, Modifiers::NONE),
+	("key_find", KeyCode::Find, Modifiers::NONE),
+	("key_help", KeyCode::Help, Modifiers::NONE),
+	("key_mark", KeyCode::Mark, Modifiers::NONE),
+	("key_message", KeyCode::Message, Modifiers::NONE),
+	("key_move", KeyCode::Move, Modifiers::NONE),
+	("key_next", KeyCode::Next, Modifiers::NONE),
+	("key_open", KeyCode::Open, Modifiers::NONE),
+	("key_options", KeyCode::Options, Modifiers::NONE),
+	("key_previous", KeyCode::Previous, Modifiers::NONE),
+	("key_print", KeyCode::Print, Modifiers::NONE),
+	("key_redo", KeyCode::Redo, Modifiers::NONE),
+	("key_reference", KeyCode::Reference, Modifiers::NONE),
+	("key_refresh", KeyCode::Refresh, Modifiers::NONE),
+	("key_replace", KeyCode::Replace, Modifiers::NONE),
+	("key_restart", KeyCode::Restart, Modifiers::NONE),
+	("key_resume", KeyCode::Resume, Modifiers::NONE),
+	("key_save", KeyCode::Save, Modifiers::NONE),
+	("key_select", KeyCode::Select, Modifiers::NONE),
+	("key_suspend", KeyCode::Suspend, Modifiers::NONE),
+	("key_undo", KeyCode::Undo, Modifiers::NONE),
+	("key_sbeg", KeyCode::Begin, Modifiers::SHIFT),
+	("key_scancel", KeyCode::Cancel, Modifiers::SHIFT),
+	("key_scommand", KeyCode::Command, Modifiers::SHIFT),
+	("key_scopy", KeyCode::Copy, Modifiers::SHIFT),
+	("key_screate", KeyCode::Create, Modifiers::SHIFT),
+	("key_sdc", KeyCode::Delete, Modifiers::SHIFT),
+	("key_sdl", KeyCode::DeleteLine, Modifiers::SHIFT),
+	("key_send", KeyCode::End, Modifiers::SHIFT),
+	("key_seol", KeyCode::ClearToEndOfLine, Modifiers::SHIFT),
+	("key_sexit", KeyCode::Exit, Modifiers::SHIFT),
+	("key_sfind", KeyCode::Find, Modifiers::SHIFT),
+	("key_shelp", KeyCode::Help, Modifiers::SHIFT),
+	("key_shome", KeyCode::Home, Modifiers::SHIFT),
+	("key_sic", KeyCode::Insert, Modifiers::SHIFT),
+	("key_sleft", KeyCode::Left, Modifiers::SHIFT),
+	("key_smessage", KeyCode::Message, Modifiers::SHIFT),
+	("key_smove", KeyCode::Move, Modifiers::SHIFT),
+	("key_snext", KeyCode::Next, Modifiers::SHIFT),
+	("key_soptions", KeyCode::Options, Modifiers::SHIFT),
+	("key_sprevious", KeyCode::Previous, Modifiers::SHIFT),
+	("key_sprint", KeyCode::Print, Modifiers::SHIFT),
+	("key_sredo", KeyCode::Redo, Modifiers::SHIFT),
+	("key_sreplace", KeyCode::Replace, Modifiers::SHIFT),
+	("key_sright", KeyCode::Right, Modifiers::SHIFT),
+	("key_srsume", KeyCode::Resume, Modifiers::SHIFT),
+	("key_ssave", KeyCode::Save, Modifiers::SHIFT),
+	("key_ssuspend", KeyCode::Suspend, Modifiers::SHIFT),
+	("key_sundo", KeyCode::Undo, Modifiers::SHIFT),
+];
+
+/// Extended key capabilities, suffixed by the xterm modifier parameter.
+const EXTENDED: &[(&str, KeyCode)] = &[
+	("kUP", KeyCode::Up),
+	("kDN", KeyCode::Down),
+	("kLFT", KeyCode::Left),
+	("kRIT", KeyCode::Right),
+	("kHOM", KeyCode::Home),
+	("kEND", KeyCode::End),
+	("kPRV", KeyCode::PageUp),
+	("kNXT", KeyCode::PageDown),
+	("kIC", KeyCode::Insert),
+	("kDC", KeyCode::Delete),
+	("kBEG", KeyCode::Begin),
+];
+
+/// Find all the keys defined by the terminal, ordered by capability name.
+fn keys(info: &Database) -> Vec<(&str, &[u8], Key)> {
+	let mut keys = info
+		.iter()
+		.filter_map(|(name, value)| match (Key::from_capability(name), value) {
+			(Some(key), Value::String(sequence)) if !sequence.is_empty() => {
+				Some((name, &sequence[..], key))
+			}
+
+			_ => None,
+		})
+		.collect::<Vec<_>>();
+
+	keys.sort_by(|a, b| a.0.cmp(b.0));
+	keys
+}
+
+/// Derive the sequence for a modified key from the unmodified one.
+fn modified(sequence: &[u8], modifiers: Modifiers) -> Option<Vec<u8>> {
+	let parameter = modifiers.to_xterm().to_string();
+
+	match sequence {
+		// `SS3` or `CSI` followed by the final byte.
+		[0x1B, b'O' | b'[', last] => {
+			Some([&b"\x1B[1;"[..], parameter.as_bytes(), &[*last]].concat())
+		}
+
+		// `CSI` with a numeric parameter followed by `~`.
+		[0x1B, b'[', number @ .., b'~']
+			if !number.is_empty() && number.iter().all(u8::is_ascii_digit) =>
+		{
+			Some([&b"\x1B["[..], number, b";", parameter.as_bytes(), b"~"].concat())
+		}
+
+		_ => None,
+	}
+}
+
+/// A node in the sequence trie.
+#[derive(Eq, PartialEq, Clone, Default, Debug)]
+struct Node {
+	key: Option<Key>,
+	children: BTreeMap<u8, Node>,
+}
+
+/// Incremental decoder of input sequences.
+///
+/// The sequences are taken from the `key_*` capabilities and the extended
+/// capabilities for modified keys (`kUP5`, `kDC3` and so on), anything else is
+/// decoded as characters, control characters, `Alt` combinations when
+/// prefixed by `ESC`, or raw bytes.
+///
+/// Mouse reports are decoded as well, see `Mouse`.
+#[derive(Eq, PartialEq, Clone, Default, Debug)]
+pub struct KeyDecoder {
+	root: Node,
+	mouse: Mouse,
+	buffer: Vec<u8>,
+}
+
+impl KeyDecoder {
+	/// Create a decoder for the keys of the given terminal.
+	///
+	/// Modified keys the terminal doesn't define are decoded following the
+	/// xterm convention, see `Key::sequence`.
+	pub fn new(info: &Database) -> Self {
+		let keys = keys(info);
+		let mut decoder = KeyDecoder { mouse: Mouse::new(info), ..Default::default() };
+
+		// The first key sending a sequence wins.
+		for &(_, sequence, key) in &keys {
+			if decoder.get(sequence).is_none() {
+				decoder.insert(sequence, key);
+			}
+		}
+
+		for &(_, sequence, key) in &keys {
+			let modifiable = match key.code {
+				KeyCode::F(number) => number <= 12,
+				code => EXTENDED.iter().any(|&(_, other)| other == code),
+			};
+
+			if !modifiable || !key.modifiers.is_empty() {
+				continue;
+			}
+
+			for parameter in 2..=8 {
+				let key = Key::new(key.code, Modifiers::from_xterm(parameter));
+
+				if let Some(sequence) = modified(sequence, key.modifiers) {
+					if decoder.get(&sequence).is_none() {
+						decoder.insert(sequence, key);
+					}
+				}
+			}
+		}
+
+		decoder
+	}
+
+	/// Decode mouse reports with the given mouse tracking.
+	pub fn mouse(mut self, mouse: Mouse) -> Self {
+		self.mouse = mouse;
+		self
+	}
+
+	/// The key decoded from the given sequence.
+	fn get(&self, sequence: &[u8]) -> Option<Key> {
+		let mut node = &self.root;
+
+		for byte in sequence {
+			node = node.children.get(byte)?;
+		}
+
+		node.key
+	}
+
+	/// Decode the given sequence as the given key.
+	pub fn insert<S: AsRef<[u8]>>(&mut self, sequence: S, key: Key) {
+		let mut node = &mut self.root;
+
+		for &byte in sequence.as_ref() {
+			node = node.children.entry(byte).or_default();
+		}
+
+		node.key = Some(key);
+	}
+
+	/// Add input to decode.
+	pub fn feed(&mut self, input: &[u8]) {
+		self.buffer.extend_from_slice(input);
+	}
+
+	/// Whether there's no pending input.
+	pub fn is_empty(&self) -> bool {
+		self.buffer.is_empty()
+	}
+
+	/// Decode the next event.
+	///
+	/// Returns `None` when the input is exhausted or when it could be the start
+	/// of a longer sequence, in which case more input has to be fed or
+	/// `flush` called if none arrives in a reasonable time.
+	pub fn decode(&mut self) -> Option<Event> {
+		self.next(false)
+	}
+
+	/// Decode the next event without waiting for more input.
+	///
+	/// This is meant to be called after a timeout, so that for example a lone
+	/// `ESC` is decoded as the escape key.
+	pub fn flush(&mut self) -> Option<Event> {
+		self.next(true)
+	}
+
+	fn next(&mut self, force: bool) -> Option<Event> {
+		if self.buffer.is_empty() {
+			return None;
+		}
+
+		let (event, length) = self.decode_at(&self.buffer, force)?;
+		self.buffer.drain(..length);
+
+		Some(event)
+	}
+
+	/// Decode the event at the start of the input, with its length in bytes.
+	fn decode_at(&self, input: &[u8], force: bool) -> Option<(Event, usize)> {
+		let mut node = &self.root;
+		let mut matched = None;
+		let mut length = 0;
+
+		while let Some(child) = input.get(length).and_then(|byte| node.children.get(byte)) {
+			node = child;
+			length += 1;
+
+			if let Some(key) = node.key {
+				matched = Some((Event::Key(key), length));
+			}
+		}
+
+		// Wait for the longest match.
+		if length == input.len() && !node.children.is_empty() && !force {
+			return None;
+		}
+
+		matched.or_else(|| self.fallback(input, force))
+	}
+
+	/// Decode an event not matching any known sequence.
+	fn fallback(&self, input: &[u8], force: bool) -> Option<(Event, usize)> {
+		fn key(code: KeyCode, modifiers: Modifiers, length: usize) -> Option<(Event, usize)> {
+			Some((Event::Key(Key::new(code, modifiers)), length))
+		}
+
+		match self.mouse.decode(input) {
+			Decoded::Report(event, length) => return Some((Event::Mouse(event), length)),
+
+			Decoded::Partial if !force => return None,
+
+			_ => (),
+		}
+
+		match input[0] {
+			0x1B => {
+				let sequence = match input.get(1) {
+					Some(b'[') => control(input),
+					Some(b'O') if input.len() > 2 => Some(3),
+					_ => None,
+				};
+
+				match (input.get(1), sequence) {
+					(_, Some(length)) => Some((Event::Raw(input[..length].to_vec()), length)),
+
+					(None, _) if force => key(KeyCode::Escape, Modifiers::NONE, 1),
+
+					(None, _) => None,
+
+					(Some(b'[' | b'O'), _) if !force => None,
+
+					(Some(b'[' | b'O'), _) if input.len() > 2 => {
+						Some((Event::Raw(input.to_vec()), input.len()))
+					}
+
+					(Some(_), _) => match self.decode_at(&input[1..], force)? {
+						(Event::Key(Key { code, modifiers }), length) => {
+							key(code, modifiers | Modifiers::ALT, length + 1)
+						}
+
+						(_, length) => Some((Event::Raw(input[..length + 1].to_vec()), length + 1)),
+					},
+				}
+			}
+
+			b'\r' | b'\n' => key(KeyCode::Enter, Modifiers::NONE, 1),
+
+			b'\t' => key(KeyCode::Tab, Modifiers::NONE, 1),
+
+			0x08 | 0x7F => key(KeyCode::Backspace, Modifiers::NONE, 1),
+
+			0x00 => key(KeyCode::Char(' '), Modifiers::CTRL, 1),
+
+			byte @ 0x01..=0x1A => key(KeyCode::Char((b'a' + byte - 1) as char), Modifiers::CTRL, 1),
+
+			byte @ 0x1C..=0x1F => key(KeyCode::Char((byte + 0x40) as char), Modifiers::CTRL, 1),
+
+			byte @ 0x20..=0x7E => key(KeyCode::Char(byte as char), Modifiers::NONE, 1),
+
+			byte => {
+				let width = match byte {
+					0xC2..=0xDF => 2,
+					0xE0..=0xEF => 3,
+					0xF0..=0xF4 => 4,
+					_ => return Some((Event::Raw(vec![byte]), 1)),
+				};
+
+				if input.len() < width {
+					return if force {
+						Some((Event::Raw(input.to_vec()), input.len()))
+					} else {
+						None
+					};
+				}
+
+				match str::from_utf8(&input[..width]).ok().and_then(|string| string.chars().next())
+				{
+					Some(ch) => key(KeyCode::Char(ch), Modifiers::NONE, width),
+
+					None => Some((Event::Raw(vec![byte]), 1)),
+				}
+			}
+		}
+	}
+}
+
+/// Find the length of the control sequence at the start of the input.
+fn control(input: &[u8]) -> Option<usize> {
+	for (index, &byte) in input.iter().enumerate().skip(2) {
+		match byte {
+			// Parameter and intermediate bytes.
+			0x20..=0x3F => (),
+
+			// Final byte.
+			0x40..=0x7E => return Some(index + 1),
+
+			// Malformed sequence.
+			_ => return Some(index),
+		}
+	}
+
+	None
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+
+	fn decode(decoder: &mut KeyDecoder, input: &[u8]) -> Vec<Event> {
+		decoder.feed(input);
+		std::iter::from_fn(|| decoder.decode()).collect()
+	}
+
+	#[test]
+	fn keys() {
+		let info = Database::from_path("tests/xterm-256color").unwrap();
+		let mut decoder = KeyDecoder::new(&info);
+
+		assert_eq!(
+			vec![
+				Event::Key(KeyCode::Up.into()),
+				Event::Key(KeyCode::F(5).into()),
+				Event::Key(Key::new(KeyCode::Right, Modifiers::CTRL)),
+				Event::Key(Key::new(KeyCode::Delete, Modifiers::ALT)),
+				Event::Key(Key::new(KeyCode::Left, Modifiers::SHIFT)),
+			],
+			decode(&mut decoder, b"\x1BOA\x1B[15~\x1B[1;5C\x1B[3;3~\x1B[1;2D")
+		);
+	}
+
+	#[test]
+	fn fallback() {
+		let mut decoder = KeyDecoder::default();
+
+		assert_eq!(
+			vec![
+				Event::Key(KeyCode::Char('é').into()),
+				Event::Key(Key::new(KeyCode::Char('c'), Modifiers::CTRL)),
+				Event::Key(Key::new(KeyCode::Char('x'), Modifiers::ALT)),
+				Event::Raw(b"\x1B[99;1x".to_vec()),
+				Event::Key(KeyCode::Enter.into()),
+			],
+			decode(&mut decoder, "é\x03\x1Bx\x1B[99;1x\r".as_bytes())
+		);
+	}
+
+	#[test]
+	fn partial() {
+		let info = Database::from_path("tests/xterm-256color").unwrap();
+		let mut decoder = KeyDecoder::new(&info);
+
+		assert!(decode(&mut decoder, b"\x1B[1;").is_empty());
+		assert_eq!(
+			vec![Event::Key(Key::new(KeyCode::Up, Modifiers::CTRL))],
+			decode(&mut decoder, b"5A")
+		);
+
+		assert!(decode(&mut decoder, b"\x1B").is_empty());
+		assert_eq!(Some(Event::Key(KeyCode::Escape.into())), decoder.flush());
+		assert!(decoder.is_empty());
+
+		assert!(decode(&mut decoder, b"\xC3").is_empty());
+		assert_eq!(vec![Event::Key(KeyCode::Char('é').into())], decode(&mut decoder, b"\xA9"));
+	}
+
+	#[test]
+	fn capabilities() {
+		assert_eq!(Some(KeyCode::F(12).into()), Key::from_capability("kf12"));
+		assert_eq!(
+			Some(Key::new(KeyCode::F(1), Modifiers::CTRL | Modifiers::SHIFT)),
+			Key::from_capability("key_f37")
+		);
+		assert_eq!(
+			Some(Key::new(KeyCode::F(3), Modifiers::ALT | Modifiers::SHIFT)),
+			Key::from_capability("kf63")
+		);
+		assert_eq!(Some(Key::new(KeyCode::Up, Modifiers::SHIFT)), Key::from_capability("kUP"));
+		assert_eq!(
+			Some(Key::new(KeyCode::Down, Modifiers::ALT | Modifiers::CTRL)),
+			Key::from_capability("kDN7")
+		);
+		assert_eq!(Some(Key::new(KeyCode::Left, Modifiers::SHIFT)), Key::from_capability("kLFT"));
+		assert_eq!(None, Key::from_capability("kUP1"));
+		assert_eq!(None, Key::from_capability("cup"));
+	}
+
+	#[test]
+	fn convention() {
+		let mut info = Database::new();
+		info.name("test")
+			.raw("kcuf1", "\x1BOC")
+			.raw("kdch1", "\x1B[3~")
+			.raw("kf1", "\x1BOP")
+			.raw("kRIT5", "\x1B[5C");
+		let info = info.build().unwrap();
+
+		let ctrl = |code| Key::new(code, Modifiers::CTRL);
+		assert_eq!(Some(b"\x1B[5C".to_vec()), ctrl(KeyCode::Right).sequence(&info));
+		assert_eq!(Some(b"\x1B[3;5~".to_vec()), ctrl(KeyCode::Delete).sequence(&info));
+		assert_eq!(Some(b"\x1B[1;5P".to_vec()), ctrl(KeyCode::F(1)).sequence(&info));
+		assert_eq!(None, ctrl(KeyCode::Up).sequence(&info));
+
+		let mut decoder = KeyDecoder::new(&info);
+		assert_eq!(
+			vec![
+				Event::Key(ctrl(KeyCode::Right)),
+				Event::Key(Key::new(KeyCode::Right, Modifiers::SHIFT)),
+				Event::Key(ctrl(KeyCode::Delete)),
+			],
+			decode(&mut decoder, b"\x1B[5C\x1B[1;2C\x1B[3;5~")
+		);
+	}
+
+	#[test]
+	fn mouse() {
+		let info = Database::from_path("tests/xterm-256color").unwrap();
+		let mut decoder = KeyDecoder::new(&info);
+
+		assert!(decode(&mut decoder, b"\x1B[<0;3;").is_empty());
+		assert_eq!(
+			vec![
+				Event::Mouse(MouseEvent {
+					action: crate::mouse::Action::Press(crate::mouse::Button::Left),
+					x: 2,
+					y: 3,
+					modifiers: Modifiers::NONE,
+				}),
+				Event::Key(KeyCode::Up.into()),
+			],
+			decode(&mut decoder, b"4M\x1BOA")
+		);
+	}
+}
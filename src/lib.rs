@@ -27,6 +27,9 @@ mod parser;
 pub mod expand;
 pub use crate::expand::Expand;
 
+/// Disassembling of parametrized strings.
+pub mod disassemble;
+
 /// Standard terminal capabilities.
 pub mod capability;
 pub use crate::capability::{Capability, Value};
@@ -36,3 +39,48 @@ pub use crate::database::Database;
 
 /// Constants to deal with name differences across terminfo and termcap.
 pub mod names;
+
+/// Decoding of terminal input.
+pub mod input;
+
+/// Mouse tracking.
+pub mod mouse;
+
+/// Alternate character set.
+pub mod acs;
+
+/// Colors downgraded to the terminal depth.
+pub mod color;
+
+/// Text styles.
+pub mod style;
+
+/// Cursor movement.
+pub mod movement;
+
+/// Tab stops.
+pub mod tabs;
+
+/// Scrolling and line insertion and deletion.
+pub mod scroll;
+
+/// Character insertion, deletion and erasure.
+pub mod edit;
+
+/// Window title and status line.
+pub mod title;
+
+/// Clipboard access.
+pub mod clipboard;
+
+/// Cursor shape, color and visibility.
+pub mod cursor;
+
+/// Guards restoring terminal modes.
+pub mod guard;
+
+/// Soft label keys.
+pub mod slk;
+
+/// Terminal palette.
+pub mod palette;
@@ -0,0 +1,414 @@
+//            DO WHAT THE FUCK YOU WANT TO PUBLIC LICENSE
+//                    Version 2, December 2004
+//
+// Copyleft (ↄ) meh. <meh@schizofreni.co> | http://meh.schizofreni.co
+//
+// Everyone is permitted to copy and distribute verbatim or modified
+// copies of this license document, and changing it is allowed as long
+// as the name is changed.
+//
+//            DO WHAT THE FUCK YOU WANT TO PUBLIC LICENSE
+//   TERMS AND CONDITIONS FOR COPYING, DISTRIBUTION AND MODIFICATION
+//
+//  0. You just DO WHAT THE FUCK YOU WANT TO.
+
+//! Mouse tracking and decoding of mouse reports.
+//!
+//! ## Example
+//!
+//! ```
+//! use terminfo::Database;
+//! use terminfo::input::Modifiers;
+//! use terminfo::mouse::{Action, Button, Decoded, Mouse, MouseEvent};
+//!
+//! let info  = Database::from_path("tests/xterm-256color").unwrap();
+//! let mouse = Mouse::new(&info);
+//!
+//! assert_eq!(b"\x1B[?1006;1000h", mouse.enable());
+//!
+//! assert_eq!(
+//!     Decoded::Report(
+//!         MouseEvent { action: Action::Press(Button::Left), x: 9, y: 4, modifiers: Modifiers::CTRL },
+//!         11,
+//!     ),
+//!     mouse.decode(b"\x1B[<16;10;5M")
+//! );
+//! ```
+
+use std::str;
+
+use crate::capability as cap;
+use crate::capability::Value;
+use crate::input::Modifiers;
+use crate::Database;
+
+/// A mouse button.
+#[derive(Eq, PartialEq, Copy, Clone, Hash, Debug)]
+pub enum Button {
+	Left,
+	Middle,
+	Right,
+
+	/// The extra buttons, starting from `8`.
+	Other(u8),
+}
+
+/// A wheel direction.
+#[derive(Eq, PartialEq, Copy, Clone, Hash, Debug)]
+pub enum Wheel {
+	Up,
+	Down,
+	Left,
+	Right,
+}
+
+/// What happened to the mouse.
+#[derive(Eq, PartialEq, Copy, Clone, Hash, Debug)]
+pub enum Action {
+	/// A button was pressed.
+	Press(Button),
+
+	/// A button was released, only SGR reports tell which one.
+	Release(Option<Button>),
+
+	/// The mouse moved, with the button held if any.
+	Motion(Option<Button>),
+
+	/// The wheel was scrolled.
+	Wheel(Wheel),
+}
+
+/// A mouse event.
+#[derive(Eq, PartialEq, Copy, Clone, Hash, Debug)]
+pub struct MouseEvent {
+	/// What happened.
+	pub action: Action,
+
+	/// The column, starting from `0`.
+	pub x: u16,
+
+	/// The row, starting from `0`.
+	pub y: u16,
+
+	/// The modifiers held.
+	pub modifiers: Modifiers,
+}
+
+/// The result of decoding a mouse report.
+#[derive(Eq, PartialEq, Copy, Clone, Debug)]
+pub enum Decoded {
+	/// A complete report and its length in bytes.
+	Report(MouseEvent, usize),
+
+	/// The start of a report, more input is needed.
+	Partial,
+
+	/// Not a report.
+	Unknown,
+}
+
+/// Mouse tracking for a terminal.
+///
+/// The enable and disable sequences come from the `XM` extended capability,
+/// defaulting to normal tracking with SGR reports. Reports are decoded in
+/// any of the X10 and normal (following `key_mouse`), UTF-8 (1005), SGR
+/// (1006) and urxvt (1015) formats.
+#[derive(Eq, PartialEq, Clone, Debug)]
+pub struct Mouse {
+	prefix: Vec<u8>,
+	enable: Vec<u8>,
+	disable: Vec<u8>,
+	utf8: bool,
+}
+
+impl Default for Mouse {
+	fn default() -> Self {
+		Mouse {
+			prefix: b"\x1B[M".to_vec(),
+			enable: b"\x1B[?1000;1006h".to_vec(),
+			disable: b"\x1B[?1000;1006l".to_vec(),
+			utf8: false,
+		}
+	}
+}
+
+impl Mouse {
+	/// Create the mouse tracking for the given terminal.
+	pub fn new(info: &Database) -> Self {
+		let mut mouse = Mouse::default();
+
+		// SGR reports are always recognized.
+		if let Some(prefix) = info.get::<cap::KeyMouse>() {
+			if prefix.as_ref() != b"\x1B[<" {
+				mouse.prefix = prefix.as_ref().to_vec();
+			}
+		}
+
+		// Screen uses `XM` as a boolean.
+		if let Some(Value::String(string)) = info.raw("XM") {
+			if let (Ok(enable), Ok(disable)) = (expand!(string; 1), expand!(string; 0)) {
+				mouse.enable = enable;
+				mouse.disable = disable;
+			}
+		}
+
+		mouse
+	}
+
+	/// Decode X10 style reports as UTF-8 (1005), this has to be set when the
+	/// mode has been enabled since the two are ambiguous.
+	pub fn utf8(mut self, value: bool) -> Self {
+		self.utf8 = value;
+		self
+	}
+
+	/// The sequence to enable mouse tracking.
+	pub fn enable(&self) -> &[u8] {
+		&self.enable
+	}
+
+	/// The sequence to disable mouse tracking.
+	pub fn disable(&self) -> &[u8] {
+		&self.disable
+	}
+
+	/// The prefix of X10 style reports.
+	pub fn prefix(&self) -> &[u8] {
+		&self.prefix
+	}
+
+	/// Decode the report at the start of the input.
+	pub fn decode(&self, input: &[u8]) -> Decoded {
+		if let Some(rest) = strip(input, b"\x1B[<") {
+			return match parameters(rest) {
+				Some((Some([code, x, y]), last @ (b'M' | b'm'), length)) => {
+					event(code, x, y, last == b'm')
+						.map_or(Decoded::Unknown, |event| Decoded::Report(event, length + 3))
+				}
+
+				Some(..) => Decoded::Unknown,
+
+				None => Decoded::Partial,
+			};
+		}
+
+		if let Some(rest) = strip(input, &self.prefix).or_else(|| strip(input, b"\x1B[M")) {
+			let prefix = input.len() - rest.len();
+			let mut values = [0; 3];
+			let mut length = 0;
+
+			for value in &mut values {
+				let (decoded, width) = match (self.utf8, rest.get(length..)) {
+					(_, Some([])) | (_, None) => return Decoded::Partial,
+
+					(true, Some(rest)) => match character(rest) {
+						Some(Some(result)) => result,
+
+						Some(None) => return Decoded::Partial,
+
+						None => return Decoded::Unknown,
+					},
+
+					(false, Some(rest)) => (u32::from(rest[0]), 1),
+				};
+
+				match decoded.checked_sub(32) {
+					Some(decoded) => *value = decoded,
+
+					None => return Decoded::Unknown,
+				}
+
+				length += width;
+			}
+
+			let [code, x, y] = values;
+
+			return match event(code, x, y, false) {
+				Some(event) => Decoded::Report(event, prefix + length),
+
+				None => Decoded::Unknown,
+			};
+		}
+
+		if let Some(rest) = strip(input, b"\x1B[") {
+			if rest.first().is_some_and(|byte| !byte.is_ascii_digit()) {
+				return Decoded::Unknown;
+			}
+
+			return match parameters(rest) {
+				Some((Some([code, x, y]), b'M', length)) => match code.checked_sub(32) {
+					Some(code) => event(code, x, y, false)
+						.map_or(Decoded::Unknown, |event| Decoded::Report(event, length + 2)),
+
+					None => Decoded::Unknown,
+				},
+
+				Some(..) => Decoded::Unknown,
+
+				None => Decoded::Partial,
+			};
+		}
+
+		Decoded::Unknown
+	}
+}
+
+/// Strip the prefix from the input, the input is a prefix itself if it's
+/// shorter.
+fn strip<'a>(input: &'a [u8], prefix: &[u8]) -> Option<&'a [u8]> {
+	if input.len() < prefix.len() {
+		if prefix.starts_with(input) {
+			return Some(&[]);
+		}
+
+		return None;
+	}
+
+	input.strip_prefix(prefix)
+}
+
+/// Parse three `;` separated numbers followed by a final byte, returns the
+/// numbers if valid, the final byte and the length including it.
+fn parameters(input: &[u8]) -> Option<(Option<[u32; 3]>, u8, usize)> {
+	let end = input.iter().position(|byte| !byte.is_ascii_digit() && *byte != b';')?;
+	let numbers = str::from_utf8(&input[..end])
+		.ok()?
+		.split(';')
+		.map(|number| number.parse().ok())
+		.collect::<Option<Vec<u32>>>();
+
+	let numbers = match numbers.as_deref() {
+		Some(&[code, x, y]) => Some([code, x, y]),
+		_ => None,
+	};
+
+	Some((numbers, input[end], end + 1))
+}
+
+/// Decode a UTF-8 character with its width, `Some(None)` if incomplete.
+fn character(input: &[u8]) -> Option<Option<(u32, usize)>> {
+	let width = match input[0] {
+		0x00..=0x7F => 1,
+		0xC2..=0xDF => 2,
+		0xE0..=0xEF => 3,
+		_ => return None,
+	};
+
+	if input.len() < width {
+		return Some(None);
+	}
+
+	let ch = str::from_utf8(&input[..width]).ok()?.chars().next()?;
+	Some(Some((ch as u32, width)))
+}
+
+/// Build an event from the button code and the 1-based coordinates.
+fn event(code: u32, x: u32, y: u32, release: bool) -> Option<MouseEvent> {
+	let mut modifiers = Modifiers::NONE;
+
+	if code & 4 != 0 {
+		modifiers |= Modifiers::SHIFT;
+	}
+
+	if code & 8 != 0 {
+		modifiers |= Modifiers::ALT;
+	}
+
+	if code & 16 != 0 {
+		modifiers |= Modifiers::CTRL;
+	}
+
+	let button = match (code & 0b1100_0000, code & 3) {
+		(0, 0) => Some(Button::Left),
+		(0, 1) => Some(Button::Middle),
+		(0, 2) => Some(Button::Right),
+		(0, _) => None,
+		(128, number) => Some(Button::Other(8 + number as u8)),
+		_ => None,
+	};
+
+	let action = if code & 32 != 0 {
+		Action::Motion(button)
+	} else if code & 0b1100_0000 == 64 {
+		Action::Wheel(match code & 3 {
+			0 => Wheel::Up,
+			1 => Wheel::Down,
+			2 => Wheel::Left,
+			_ => Wheel::Right,
+		})
+	} else if release || button.is_none() {
+		Action::Release(button)
+	} else {
+		Action::Press(button?)
+	};
+
+	Some(MouseEvent {
+		action,
+		x: u16::try_from(x.checked_sub(1)?).ok()?,
+		y: u16::try_from(y.checked_sub(1)?).ok()?,
+		modifiers,
+	})
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+
+	fn report(action: Action, x: u16, y: u16, modifiers: Modifiers, length: usize) -> Decoded {
+		Decoded::Report(MouseEvent { action, x, y, modifiers }, length)
+	}
+
+	#[test]
+	fn sequences() {
+		let info = Database::from_path("tests/xterm-256color").unwrap();
+		let mouse = Mouse::new(&info);
+		assert_eq!(b"\x1B[?1006;1000h", mouse.enable());
+		assert_eq!(b"\x1B[?1006;1000l", mouse.disable());
+
+		let info = Database::from_path("tests/cancer-256color").unwrap();
+		let mouse = Mouse::new(&info);
+		assert_eq!(b"\x1B[M", mouse.prefix());
+		assert_eq!(b"\x1B[?1000;1006h", mouse.enable());
+	}
+
+	#[test]
+	fn formats() {
+		let mouse = Mouse::default();
+		let none = Modifiers::NONE;
+
+		assert_eq!(report(Action::Press(Button::Left), 0, 1, none, 6), mouse.decode(b"\x1B[M !\""));
+		assert_eq!(report(Action::Release(None), 2, 2, none, 6), mouse.decode(b"\x1B[M###"));
+		assert_eq!(
+			report(Action::Press(Button::Left), 199, 0, none, 7),
+			mouse.clone().utf8(true).decode("\x1B[M \u{E8}!".as_bytes())
+		);
+		assert_eq!(
+			report(Action::Release(Some(Button::Right)), 9, 19, Modifiers::SHIFT, 11),
+			mouse.decode(b"\x1B[<6;10;20m")
+		);
+		assert_eq!(
+			report(Action::Wheel(Wheel::Down), 0, 0, none, 10),
+			mouse.decode(b"\x1B[<65;1;1M")
+		);
+		assert_eq!(
+			report(Action::Motion(Some(Button::Middle)), 4, 5, Modifiers::ALT, 10),
+			mouse.decode(b"\x1B[<41;5;6Mx")
+		);
+		assert_eq!(
+			report(Action::Press(Button::Left), 4, 5, none, 9),
+			mouse.decode(b"\x1B[32;5;6M")
+		);
+	}
+
+	#[test]
+	fn invalid() {
+		let mouse = Mouse::default();
+
+		assert_eq!(Decoded::Partial, mouse.decode(b"\x1B["));
+		assert_eq!(Decoded::Partial, mouse.decode(b"\x1B[<0;1"));
+		assert_eq!(Decoded::Partial, mouse.decode(b"\x1B[M !"));
+		assert_eq!(Decoded::Unknown, mouse.decode(b"\x1B[1;5A"));
+		assert_eq!(Decoded::Unknown, mouse.decode(b"\x1B[A"));
+		assert_eq!(Decoded::Unknown, mouse.decode(b"\x1B[<0;0;1M"));
+	}
+}
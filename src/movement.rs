@@ -0,0 +1,436 @@
+//            DO WHAT THE FUCK YOU WANT TO PUBLIC LICENSE
+//                    Version 2, December 2004
+//
+// Copyleft (ↄ) meh. <meh@schizofreni.co> | http://meh.schizofreni.co
+//
+// Everyone is permitted to copy and distribute verbatim or modified
+// copies of this license document, and changing it is allowed as long
+// as the name is changed.
+//
+//            DO WHAT THE FUCK YOU WANT TO PUBLIC LICENSE
+//   TERMS AND CONDITIONS FOR COPYING, DISTRIBUTION AND MODIFICATION
+//
+//  0. You just DO WHAT THE FUCK YOU WANT TO.
+
+//! Cursor movement optimization, like ncurses' `mvcur`.
+//!
+//! ## Example
+//!
+//! ```
+//! use terminfo::Database;
+//! use terminfo::movement::Movement;
+//! use terminfo::style::Style;
+//!
+//! let info     = Database::from_path("tests/xterm-256color").unwrap();
+//! let movement = Movement::new(&info);
+//! let style    = Style::default();
+//!
+//! assert_eq!(b"\x08".to_vec(), movement.sequence(Some((10, 5)), (9, 5), &style).unwrap().unwrap());
+//! assert_eq!(b"\r\n".to_vec(), movement.sequence(Some((10, 5)), (0, 6), &style).unwrap().unwrap());
+//! assert_eq!(b"\x1B[21;41H".to_vec(), movement.sequence(None, (40, 20), &style).unwrap().unwrap());
+//! ```
+
+use std::io::Write;
+use std::num::NonZeroU16;
+
+use crate::capability as cap;
+use crate::error;
+use crate::expand::Scratch;
+use crate::style::{Renderer, Style};
+use crate::Database;
+
+/// Expand the capability if present, with the given parameters.
+macro_rules! expand {
+	($info:expr, $scratch:expr, $cap:ty $(; $($name:ident($value:expr)),+)?) => {
+		$info
+			.get::<$cap>()
+			.map(|cap| cap.expand()$($(.$name($value.into()))+)?.scratch(&mut *$scratch).to_vec())
+			.transpose()?
+	};
+}
+
+/// Cursor movement for a terminal.
+///
+/// Positions are `(x, y)` starting from `0`, the cheapest sequence is picked
+/// among absolute addressing, `cursor_home` or `carriage_return` followed by
+/// a relative move, and a relative move from the current position.
+#[derive(Debug)]
+pub struct Movement<'a> {
+	info: &'a Database,
+	renderer: Renderer<'a>,
+	columns: u16,
+	lines: Option<u16>,
+	tabs: Option<NonZeroU16>,
+	am: bool,
+	xenl: bool,
+	msgr: bool,
+}
+
+impl<'a> Movement<'a> {
+	/// Create the cursor movement for the given terminal.
+	pub fn new(info: &'a Database) -> Self {
+		let tabs = match (info.get::<cap::Tab>(), info.get::<cap::InitTabs>()) {
+			(Some(_), Some(cap)) => NonZeroU16::new(cap.0.max(0) as u16),
+			_ => None,
+		};
+
+		Movement {
+			info,
+			renderer: Renderer::new(info),
+			columns: info.get::<cap::Columns>().map_or(80, |cap| cap.0 as u16),
+			lines: info.get::<cap::Lines>().map(|cap| cap.0 as u16),
+			tabs,
+			am: info.get::<cap::AutoRightMargin>().is_some_and(|cap| cap.0),
+			xenl: info.get::<cap::EatNewlineGlitch>().is_some_and(|cap| cap.0),
+			msgr: info.get::<cap::MoveStandoutMode>().is_some_and(|cap| cap.0),
+		}
+	}
+
+	/// Use the given screen width instead of `columns`.
+	pub fn columns(mut self, columns: u16) -> Self {
+		self.columns = columns;
+		self
+	}
+
+	/// Use the given screen height instead of `lines`.
+	pub fn lines(mut self, lines: u16) -> Self {
+		self.lines = Some(lines);
+		self
+	}
+
+	/// Write the cheapest sequence to move the cursor, returning whether the
+	/// terminal can move there.
+	pub fn to<W: Write>(
+		&self,
+		mut output: W,
+		from: Option<(u16, u16)>,
+		to: (u16, u16),
+		style: &Style,
+	) -> error::Result<bool> {
+		match self.sequence(from, to, style)? {
+			Some(sequence) => {
+				output.write_all(&sequence)?;
+				Ok(true)
+			}
+
+			None => Ok(false),
+		}
+	}
+
+	/// Find the cheapest sequence to move the cursor.
+	///
+	/// The current position is `None` when unknown, and its column is past the
+	/// last one when a character was just written there. Without
+	/// `move_standout_mode` the style is turned off while moving.
+	///
+	/// Returns `None` when the terminal cannot move there.
+	pub fn sequence(
+		&self,
+		from: Option<(u16, u16)>,
+		to: (u16, u16),
+		style: &Style,
+	) -> error::Result<Option<Vec<u8>>> {
+		let from = match from {
+			// With `eat_newline_glitch` the position after the last column is
+			// unreliable, while wrapping on the last row scrolls the screen.
+			Some((x, y)) if x >= self.columns => match (self.am, self.xenl) {
+				(true, false) => match self.lines {
+					Some(lines) if y.saturating_add(1) >= lines => {
+						Some((0, lines.saturating_sub(1)))
+					}
+
+					_ => y.checked_add(1).map(|y| (0, y)),
+				},
+
+				(false, _) => Some((self.columns.saturating_sub(1), y)),
+				(true, true) => None,
+			},
+
+			from => from,
+		};
+
+		if from == Some(to) {
+			return Ok(Some(Vec::new()));
+		}
+
+		let mut scratch = Scratch::default();
+		let scratch = &mut scratch;
+
+		let address = expand!(self.info, scratch, cap::CursorAddress; y(to.1), x(to.0));
+		let home = match expand!(self.info, scratch, cap::CursorHome) {
+			Some(home) => self.after(scratch, home, (0, 0), to)?,
+			None => None,
+		};
+		let cr = match (from, expand!(self.info, scratch, cap::CarriageReturn)) {
+			(Some((_, y)), Some(cr)) => self.after(scratch, cr, (0, y), to)?,
+			_ => None,
+		};
+		let relative = match from {
+			Some(from) => self.relative(scratch, from, to)?,
+			None => None,
+		};
+
+		let sequence =
+			match [address, home, cr, relative].into_iter().flatten().min_by_key(Vec::len) {
+				Some(sequence) => sequence,
+				None => return Ok(None),
+			};
+
+		let mut output = Vec::new();
+		let plain = Style::default();
+		let reset = !self.msgr && !self.renderer.effective(style).attributes.is_empty();
+
+		if reset {
+			self.renderer.transition(&mut output, style, &plain)?;
+		}
+
+		output.extend(sequence);
+
+		if reset {
+			self.renderer.transition(&mut output, &plain, style)?;
+		}
+
+		Ok(Some(output))
+	}
+
+	/// Prepend a prefix to a relative move.
+	fn after(
+		&self,
+		scratch: &mut Scratch,
+		mut prefix: Vec<u8>,
+		from: (u16, u16),
+		to: (u16, u16),
+	) -> error::Result<Option<Vec<u8>>> {
+		Ok(self.relative(scratch, from, to)?.map(|sequence| {
+			prefix.extend(sequence);
+			prefix
+		}))
+	}
+
+	/// The cheapest relative move.
+	fn relative(
+		&self,
+		scratch: &mut Scratch,
+		from: (u16, u16),
+		to: (u16, u16),
+	) -> error::Result<Option<Vec<u8>>> {
+		let vertical = self.vertical(scratch, from.1, to.1)?;
+		let horizontal = self.horizontal(scratch, from.0, to.0)?;
+
+		Ok(vertical.zip(horizontal).map(|(mut vertical, horizontal)| {
+			vertical.extend(horizontal);
+			vertical
+		}))
+	}
+
+	/// The cheapest vertical move.
+	fn vertical(
+		&self,
+		scratch: &mut Scratch,
+		from: u16,
+		to: u16,
+	) -> error::Result<Option<Vec<u8>>> {
+		let candidates = if to > from {
+			[
+				expand!(self.info, scratch, cap::ParmDownCursor; count(to - from)),
+				repeat(expand!(self.info, scratch, cap::CursorDown), to - from),
+			]
+		} else if to < from {
+			[
+				expand!(self.info, scratch, cap::ParmUpCursor; count(from - to)),
+				repeat(expand!(self.info, scratch, cap::CursorUp), from - to),
+			]
+		} else {
+			return Ok(Some(Vec::new()));
+		};
+
+		Ok(candidates
+			.into_iter()
+			.chain([expand!(self.info, scratch, cap::RowAddress; y(to))])
+			.flatten()
+			.min_by_key(Vec::len))
+	}
+
+	/// The cheapest horizontal move.
+	fn horizontal(
+		&self,
+		scratch: &mut Scratch,
+		from: u16,
+		to: u16,
+	) -> error::Result<Option<Vec<u8>>> {
+		if to == from {
+			return Ok(Some(Vec::new()));
+		}
+
+		let mut candidates = vec![expand!(self.info, scratch, cap::ColumnAddress; x(to))];
+
+		if to > from {
+			candidates.push(self.right(scratch, from, to)?);
+
+			if let Some(width) = self.tabs {
+				let mut tabs = 0;
+				let mut x = from;
+
+				while (x / width + 1) * width.get() <= to {
+					x = (x / width + 1) * width.get();
+					tabs += 1;
+				}
+
+				if tabs > 0 {
+					let tab = repeat(expand!(self.info, scratch, cap::Tab), tabs);
+					candidates.push(self.then(scratch, tab, x, to)?);
+				}
+			}
+		} else {
+			candidates.push(expand!(self.info, scratch, cap::ParmLeftCursor; count(from - to)));
+			candidates.push(repeat(expand!(self.info, scratch, cap::CursorLeft), from - to));
+
+			if let Some(width) = self.tabs {
+				let mut tabs = 0;
+				let mut x = from;
+
+				while x > to {
+					x = (x - 1) / width * width.get();
+					tabs += 1;
+				}
+
+				let tab = repeat(expand!(self.info, scratch, cap::BackTab), tabs);
+				candidates.push(self.then(scratch, tab, x, to)?);
+			}
+		}
+
+		Ok(candidates.into_iter().flatten().min_by_key(Vec::len))
+	}
+
+	/// Follow tabs with a move to the right.
+	fn then(
+		&self,
+		scratch: &mut Scratch,
+		tabs: Option<Vec<u8>>,
+		from: u16,
+		to: u16,
+	) -> error::Result<Option<Vec<u8>>> {
+		let mut tabs = match tabs {
+			Some(tabs) => tabs,
+			None => return Ok(None),
+		};
+
+		Ok(self.right(scratch, from, to)?.map(|sequence| {
+			tabs.extend(sequence);
+			tabs
+		}))
+	}
+
+	/// The cheapest move to the right without addressing.
+	fn right(&self, scratch: &mut Scratch, from: u16, to: u16) -> error::Result<Option<Vec<u8>>> {
+		if to == from {
+			return Ok(Some(Vec::new()));
+		}
+
+		Ok([
+			expand!(self.info, scratch, cap::ParmRightCursor; count(to - from)),
+			repeat(expand!(self.info, scratch, cap::CursorRight), to - from),
+		]
+		.into_iter()
+		.flatten()
+		.min_by_key(Vec::len))
+	}
+}
+
+/// Repeat a single step move.
+fn repeat(sequence: Option<Vec<u8>>, count: u16) -> Option<Vec<u8>> {
+	Some(sequence?.repeat(count as usize))
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+	use crate::style::Attributes;
+
+	#[test]
+	fn cheapest() {
+		let info = Database::from_path("tests/xterm-256color").unwrap();
+		let movement = Movement::new(&info);
+		let style = Style::default();
+		let sequence = |from, to| movement.sequence(from, to, &style).unwrap().unwrap();
+
+		assert_eq!(b"".to_vec(), sequence(Some((3, 3)), (3, 3)));
+		assert_eq!(b"\x1B[C".to_vec(), sequence(Some((3, 3)), (4, 3)));
+		assert_eq!(b"\x1B[16G".to_vec(), sequence(Some((3, 3)), (15, 3)));
+		assert_eq!(b"\t".to_vec(), sequence(Some((3, 3)), (8, 3)));
+		assert_eq!(b"\x1B[2A".to_vec(), sequence(Some((3, 3)), (3, 1)));
+		assert_eq!(b"\x1B[H".to_vec(), sequence(Some((30, 30)), (0, 0)));
+		assert_eq!(b"\x1B[27B".to_vec(), sequence(Some((3, 3)), (3, 30)));
+		assert_eq!(b"\x1B[97C".to_vec(), sequence(Some((3, 3)), (100, 3)));
+		assert_eq!(b"\r\t".to_vec(), sequence(Some((10, 3)), (8, 3)));
+	}
+
+	#[test]
+	fn margins() {
+		let info = Database::from_path("tests/xterm-256color").unwrap();
+		let movement = Movement::new(&info);
+		let style = Style::default();
+
+		// The position is unknown with `eat_newline_glitch`.
+		assert_eq!(
+			b"\x1B[5;1H".to_vec(),
+			movement.sequence(Some((80, 3)), (0, 4), &style).unwrap().unwrap()
+		);
+
+		let mut info = Database::new();
+		info.name("test")
+			.raw("am", ())
+			.raw("cup", "\x1B[%i%p1%d;%p2%dH")
+			.raw("cr", "\r")
+			.raw("cud1", "\n")
+			.raw("cuu1", "\x1B[A")
+			.raw("lines", 24)
+			.raw("smso", "\x1B[7m")
+			.raw("rmso", "\x1B[27m");
+		let info = info.build().unwrap();
+		let movement = Movement::new(&info).columns(10);
+
+		assert_eq!(
+			b"\n".to_vec(),
+			movement.sequence(Some((10, 3)), (0, 5), &style).unwrap().unwrap()
+		);
+
+		assert_eq!(
+			b"\x1B[1;1H".to_vec(),
+			movement.sequence(Some((10, u16::MAX)), (0, 0), &style).unwrap().unwrap()
+		);
+
+		// Wrapping on the last row scrolls.
+		assert_eq!(
+			b"\x1B[A".to_vec(),
+			movement.sequence(Some((10, 23)), (0, 22), &style).unwrap().unwrap()
+		);
+
+		let standout = Style::default().attributes(Attributes::STANDOUT);
+		assert_eq!(
+			b"\x1B[27m\r\x1B[7m".to_vec(),
+			movement.sequence(Some((5, 3)), (0, 3), &standout).unwrap().unwrap()
+		);
+		assert_eq!(
+			b"".to_vec(),
+			movement.sequence(Some((5, 3)), (5, 3), &standout).unwrap().unwrap()
+		);
+
+		let mut info = Database::new();
+		info.name("test")
+			.raw("cr", "\r")
+			.raw("cud1", "\n")
+			.raw("ht", "\t")
+			.raw("cbt", "\x1B[Z")
+			.raw("it", 8);
+		let info = info.build().unwrap();
+		let movement = Movement::new(&info);
+
+		assert_eq!(None, movement.sequence(Some((3, 3)), (3, 2), &style).unwrap());
+		assert_eq!(None, movement.sequence(None, (0, 0), &style).unwrap());
+		assert_eq!(
+			b"\r\n".to_vec(),
+			movement.sequence(Some((3, 3)), (0, 4), &style).unwrap().unwrap()
+		);
+	}
+}
@@ -0,0 +1,282 @@
+//            DO WHAT THE FUCK YOU WANT TO PUBLIC LICENSE
+//                    Version 2, December 2004
+//
+// Copyleft (ↄ) meh. <meh@schizofreni.co> | http://meh.schizofreni.co
+//
+// Everyone is permitted to copy and distribute verbatim or modified
+// copies of this license document, and changing it is allowed as long
+// as the name is changed.
+//
+//            DO WHAT THE FUCK YOU WANT TO PUBLIC LICENSE
+//   TERMS AND CONDITIONS FOR COPYING, DISTRIBUTION AND MODIFICATION
+//
+//  0. You just DO WHAT THE FUCK YOU WANT TO.
+
+//! Redefinition of the terminal palette.
+//!
+//! ## Example
+//!
+//! ```
+//! use terminfo::Database;
+//! use terminfo::palette::Palette;
+//!
+//! let info    = Database::from_path("tests/xterm-256color").unwrap();
+//! let palette = Palette::new(&info);
+//!
+//! let mut output = Vec::new();
+//! assert!(palette.initialize_color(&mut output, 1, (255, 255, 255)).unwrap());
+//! assert!(palette.restore(&mut output).unwrap());
+//! assert!(output.starts_with(b"\x1B]4;1;rgb:FF/FF/FF\x1B\\"));
+//! ```
+
+use std::io::Write;
+
+use crate::capability as cap;
+use crate::error;
+use crate::Database;
+
+/// Expand the capability to the output if present, evaluating to whether it
+/// was.
+macro_rules! put {
+	($info:expr, $output:expr, $cap:ty $(; $($name:ident($($value:expr),+)),+)?) => {
+		match $info.get::<$cap>() {
+			Some(cap) => {
+				cap.expand()$($(.$name($($value.into()),+))+)?.to($output)?;
+				true
+			}
+
+			None => false,
+		}
+	};
+}
+
+/// The palette of a terminal.
+///
+/// Operations return whether the terminal supports them.
+#[derive(Clone, Debug)]
+pub struct Palette<'a> {
+	info: &'a Database,
+	hls: bool,
+}
+
+impl<'a> Palette<'a> {
+	/// Load the palette of the given terminal.
+	pub fn new(info: &'a Database) -> Self {
+		Palette { info, hls: info.get::<cap::HueLightnessSaturation>().is_some_and(|cap| cap.0) }
+	}
+
+	/// Whether the colors can be redefined.
+	pub fn can_change(&self) -> bool {
+		self.info.get::<cap::CanChange>().is_some_and(|cap| cap.0)
+			&& self.info.get::<cap::InitializeColor>().is_some()
+	}
+
+	/// Whether colors are given in HLS instead of RGB.
+	pub fn is_hls(&self) -> bool {
+		self.hls
+	}
+
+	/// The number of colors, if any.
+	pub fn colors(&self) -> Option<u32> {
+		self.info.get::<cap::MaxColors>().map(|cap| cap.0.max(0) as u32)
+	}
+
+	/// The number of color pairs, if any.
+	pub fn pairs(&self) -> Option<u32> {
+		self.info.get::<cap::MaxPairs>().map(|cap| cap.0.max(0) as u32)
+	}
+
+	/// Redefine a color with `initialize_color`.
+	pub fn initialize_color<W: Write>(
+		&self,
+		output: W,
+		index: u16,
+		color: (u8, u8, u8),
+	) -> error::Result<bool> {
+		if !self.can_change() || self.colors().is_some_and(|colors| u32::from(index) >= colors) {
+			return Ok(false);
+		}
+
+		let (a, b, c) = self.convert(color);
+		Ok(put!(self.info, output, cap::InitializeColor; parameters(index, a, b, c)))
+	}
+
+	/// Define a color pair with `initialize_pair`, for terminals that define
+	/// pairs by their colors.
+	pub fn initialize_pair<W: Write>(
+		&self,
+		output: W,
+		pair: u16,
+		foreground: (u8, u8, u8),
+		background: (u8, u8, u8),
+	) -> error::Result<bool> {
+		if self.pairs().is_some_and(|pairs| u32::from(pair) >= pairs) {
+			return Ok(false);
+		}
+
+		let (fa, fb, fc) = self.convert(foreground);
+		let (ba, bb, bc) = self.convert(background);
+
+		Ok(put!(self.info, output, cap::InitializePair; parameters(pair, fa, fb, fc, ba, bb, bc)))
+	}
+
+	/// Switch to a color pair with `set_color_pair`.
+	pub fn set_color_pair<W: Write>(&self, output: W, pair: u16) -> error::Result<bool> {
+		Ok(put!(self.info, output, cap::SetColorPair; pair(pair)))
+	}
+
+	/// Restore the original colors with `orig_colors`, and the original pair
+	/// with `orig_pair`.
+	pub fn restore<W: Write>(&self, mut output: W) -> error::Result<bool> {
+		let colors = put!(self.info, &mut output, cap::OrigColors);
+		let pair = put!(self.info, &mut output, cap::OrigPair);
+
+		Ok(colors || pair)
+	}
+
+	/// Convert a color to the parameters the terminal expects.
+	fn convert(&self, (r, g, b): (u8, u8, u8)) -> (u16, u16, u16) {
+		let (r, g, b) = (scale(r), scale(g), scale(b));
+
+		if self.hls {
+			rgb_to_hls(r, g, b)
+		} else {
+			(r, g, b)
+		}
+	}
+}
+
+/// Scale a color component to the `0..=1000` range terminfo uses.
+pub fn scale(value: u8) -> u16 {
+	((value as u32 * 1000 + 127) / 255) as u16
+}
+
+/// Convert RGB components in `0..=1000` to hue in `0..360`, and lightness and
+/// saturation in `0..=100`, as ncurses does.
+///
+/// Like ncurses, hue starts from blue, so red is at `120` and green at `240`.
+pub fn rgb_to_hls(r: u16, g: u16, b: u16) -> (u16, u16, u16) {
+	let (r, g, b) = (r as i32, g as i32, b as i32);
+	let min = r.min(g).min(b);
+	let max = r.max(g).max(b);
+	let l = (min + max) / 20;
+
+	if min == max {
+		return (0, l as u16, 0);
+	}
+
+	let s = if l < 50 {
+		((max - min) * 100) / (max + min)
+	} else {
+		((max - min) * 100) / (2000 - max - min)
+	};
+
+	let t = if r == max {
+		120 + ((g - b) * 60) / (max - min)
+	} else if g == max {
+		240 + ((b - r) * 60) / (max - min)
+	} else {
+		360 + ((r - g) * 60) / (max - min)
+	};
+
+	((t % 360) as u16, l as u16, s as u16)
+}
+
+/// Convert hue in `0..360`, and lightness and saturation in `0..=100`, to RGB
+/// components in `0..=1000`, inverting [`rgb_to_hls`].
+pub fn hls_to_rgb(h: u16, l: u16, s: u16) -> (u16, u16, u16) {
+	let l = l.min(100) as f64 / 100.0;
+	let s = s.min(100) as f64 / 100.0;
+
+	if s == 0.0 {
+		let level = (l * 1000.0).round() as u16;
+		return (level, level, level);
+	}
+
+	let q = if l < 0.5 { l * (1.0 + s) } else { l + s - l * s };
+	let p = 2.0 * l - q;
+
+	// Shift the hue back from blue to red as the origin.
+	let h = ((h % 360) as f64 + 240.0) % 360.0 / 360.0;
+
+	let component = |t: f64| {
+		let t = t.rem_euclid(1.0);
+
+		let value = if t < 1.0 / 6.0 {
+			p + (q - p) * 6.0 * t
+		} else if t < 1.0 / 2.0 {
+			q
+		} else if t < 2.0 / 3.0 {
+			p + (q - p) * (2.0 / 3.0 - t) * 6.0
+		} else {
+			p
+		};
+
+		(value * 1000.0).round() as u16
+	};
+
+	(component(h + 1.0 / 3.0), component(h), component(h - 1.0 / 3.0))
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+
+	#[test]
+	fn hls() {
+		assert_eq!((0, 0, 0), rgb_to_hls(0, 0, 0));
+		assert_eq!((0, 100, 0), rgb_to_hls(1000, 1000, 1000));
+		assert_eq!((120, 50, 100), rgb_to_hls(1000, 0, 0));
+		assert_eq!((240, 50, 100), rgb_to_hls(0, 1000, 0));
+		assert_eq!((0, 50, 100), rgb_to_hls(0, 0, 1000));
+		assert_eq!((180, 50, 100), rgb_to_hls(1000, 1000, 0));
+
+		assert_eq!((1000, 0, 0), hls_to_rgb(120, 50, 100));
+		assert_eq!((0, 1000, 0), hls_to_rgb(240, 50, 100));
+		assert_eq!((0, 0, 1000), hls_to_rgb(0, 50, 100));
+		assert_eq!((500, 500, 500), hls_to_rgb(42, 50, 0));
+	}
+
+	#[test]
+	fn limits() {
+		let info = Database::from_path("tests/xterm-256color").unwrap();
+		let palette = Palette::new(&info);
+
+		assert_eq!(Some(256), palette.colors());
+		assert_eq!(Some(0x10000), palette.pairs());
+
+		let mut info = Database::new();
+		info.name("test").raw("colors", 0x1000000).raw("pairs", 0x10000).raw("initp", "%p1%d");
+		let info = info.build().unwrap();
+		let palette = Palette::new(&info);
+
+		let mut output = Vec::new();
+		assert_eq!(Some(0x1000000), palette.colors());
+		assert!(palette.initialize_pair(&mut output, u16::MAX, (0, 0, 0), (0, 0, 0)).unwrap());
+		assert_eq!(b"65535".to_vec(), output);
+	}
+
+	#[test]
+	fn palette() {
+		let mut info = Database::new();
+		info.name("test")
+			.raw("ccc", ())
+			.raw("hls", ())
+			.raw("colors", 8)
+			.raw("pairs", 64)
+			.raw("initc", "<%p1%d:%p2%d,%p3%d,%p4%d>")
+			.raw("initp", "[%p1%d:%p2%d,%p5%d]")
+			.raw("scp", "(%p1%d)")
+			.raw("oc", "!");
+		let info = info.build().unwrap();
+		let palette = Palette::new(&info);
+
+		let mut output = Vec::new();
+		assert!(palette.initialize_color(&mut output, 1, (255, 0, 0)).unwrap());
+		assert!(!palette.initialize_color(&mut output, 8, (255, 0, 0)).unwrap());
+		assert!(palette.initialize_pair(&mut output, 2, (0, 255, 0), (0, 0, 255)).unwrap());
+		assert!(palette.set_color_pair(&mut output, 2).unwrap());
+		assert!(palette.restore(&mut output).unwrap());
+		assert_eq!(b"<1:120,50,100>[2:240,0](2)!".to_vec(), output);
+	}
+}
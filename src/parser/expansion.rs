@@ -22,22 +22,39 @@ use nom::combinator::{map, opt, value};
 use nom::error::{make_error, ErrorKind};
 use nom::IResult;
 
+/// A single item of a parametrized string.
 #[derive(Eq, PartialEq, Copy, Clone, Debug)]
 pub enum Item<'a> {
+	/// A literal string, printed as is.
 	String(&'a [u8]),
+
+	/// A constant pushed on the stack.
 	Constant(Constant),
+
+	/// A parameter or variable access.
 	Variable(Variable),
+
+	/// An operation on the stack.
 	Operation(Operation),
+
+	/// Part of a conditional.
 	Conditional(Conditional),
+
+	/// Pop and print a value.
 	Print(Print),
 }
 
+/// A constant, `%'c'` or `%{nn}`.
 #[derive(Eq, PartialEq, Copy, Clone, Debug)]
 pub enum Constant {
 	Character(u8),
 	Integer(i32),
 }
 
+/// A variable access.
+///
+/// The `bool` in `Set` and `Get` is `true` for dynamic (`a` to `z`)
+/// variables and `false` for static (`A` to `Z`) ones.
 #[derive(Eq, PartialEq, Copy, Clone, Debug)]
 pub enum Variable {
 	Length,
@@ -46,6 +63,7 @@ pub enum Variable {
 	Get(bool, u8),
 }
 
+/// An operation on the stack.
 #[derive(Eq, PartialEq, Copy, Clone, Debug)]
 pub enum Operation {
 	Increment,
@@ -53,12 +71,16 @@ pub enum Operation {
 	Binary(Binary),
 }
 
+/// An unary operation, logical (`%!`) or bitwise (`%~`) negation.
+#[allow(clippy::upper_case_acronyms)]
 #[derive(Eq, PartialEq, Copy, Clone, Debug)]
 pub enum Unary {
 	Not,
 	NOT,
 }
 
+/// A binary operation, uppercase variants are the bitwise ones.
+#[allow(clippy::upper_case_acronyms)]
 #[derive(Eq, PartialEq, Copy, Clone, Debug)]
 pub enum Binary {
 	Add,
@@ -79,6 +101,7 @@ pub enum Binary {
 	Lesser,
 }
 
+/// Part of a conditional, `%?`, `%t`, `%e` and `%;`.
 #[derive(Eq, PartialEq, Copy, Clone, Debug)]
 pub enum Conditional {
 	If,
@@ -87,12 +110,15 @@ pub enum Conditional {
 	End,
 }
 
+/// A print item, `%[[:]flags][width[.precision]][doxXsc]`.
 #[derive(Eq, PartialEq, Copy, Clone, Debug)]
 pub struct Print {
 	pub flags: Flags,
 	pub format: Format,
 }
 
+/// The format of a print item.
+#[allow(clippy::upper_case_acronyms)]
 #[derive(Eq, PartialEq, Copy, Clone, Debug)]
 pub enum Format {
 	Chr,
@@ -104,6 +130,7 @@ pub enum Format {
 	HEX,
 }
 
+/// The flags of a print item.
 #[derive(Eq, PartialEq, Copy, Clone, Default, Debug)]
 pub struct Flags {
 	pub width: usize,
@@ -159,7 +186,10 @@ fn variable(input: &[u8]) -> IResult<&[u8], Item<'_>> {
 	match c {
 		b"l" => Ok((input, Item::Variable(Variable::Length))),
 
-		b"p" => map(one_of("123456789"), |n| Item::Variable(Variable::Push(n as u8 - b'1')))(input),
+		b"p" => alt((
+			map(one_of("123456789"), |n| Item::Variable(Variable::Push(n as u8 - b'1'))),
+			parameter,
+		))(input),
 
 		b"P" => alt((
 			map(one_of("abcdefghijklmnopqrstuvwxyz"), |n| {
@@ -183,6 +213,18 @@ fn variable(input: &[u8]) -> IResult<&[u8], Item<'_>> {
 	}
 }
 
+/// Parameters past the ninth, `%p{nn}`, an extension ncurses never produces.
+fn parameter(input: &[u8]) -> IResult<&[u8], Item<'_>> {
+	let (rest, _) = tag("{")(input)?;
+	let (rest, digits) = take_while(is_digit)(rest)?;
+	let (rest, _) = tag("}")(rest)?;
+
+	match number(digits) {
+		n @ 1..=256 => Ok((rest, Item::Variable(Variable::Push((n - 1) as u8)))),
+		_ => Err(nom::Err::Error(make_error(input, ErrorKind::Digit))),
+	}
+}
+
 fn operation(input: &[u8]) -> IResult<&[u8], Item<'_>> {
 	let (input, c) = take(1_usize)(input)?;
 	match c {
@@ -306,6 +348,12 @@ mod test {
 		test!(b"%p1" =>
 			Variable(Variable::Push(0)));
 
+		test!(b"%p10" =>
+			Variable(Variable::Push(0)));
+
+		test!(b"%p{12}" =>
+			Variable(Variable::Push(11)));
+
 		test!(b"%Pa" =>
 			Variable(Variable::Set(true, 0)));
 
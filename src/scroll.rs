@@ -0,0 +1,432 @@
+//            DO WHAT THE FUCK YOU WANT TO PUBLIC LICENSE
+//                    Version 2, December 2004
+//
+// Copyleft (ↄ) meh. <meh@schizofreni.co> | http://meh.schizofreni.co
+//
+// Everyone is permitted to copy and distribute verbatim or modified
+// copies of this license document, and changing it is allowed as long
+// as the name is changed.
+//
+//            DO WHAT THE FUCK YOU WANT TO PUBLIC LICENSE
+//   TERMS AND CONDITIONS FOR COPYING, DISTRIBUTION AND MODIFICATION
+//
+//  0. You just DO WHAT THE FUCK YOU WANT TO.
+
+//! Scrolling and line insertion and deletion.
+//!
+//! ## Example
+//!
+//! ```
+//! use terminfo::Database;
+//! use terminfo::scroll::Scroll;
+//!
+//! let info   = Database::from_path("tests/xterm-256color").unwrap();
+//! let scroll = Scroll::new(&info);
+//!
+//! let mut output = Vec::new();
+//! scroll.scroll_up(&mut output, 2).unwrap();
+//! assert_eq!(b"\x1B[24;1H\x1B[2S".to_vec(), output);
+//!
+//! let mut output = Vec::new();
+//! scroll.region(5, 10).scroll_down(&mut output, 1).unwrap();
+//! assert_eq!(b"\x1B7\x1B[6;11r\x1B[6;1H\x1BM\x1B[1;24r\x1B8".to_vec(), output);
+//! ```
+
+use std::io::Write;
+
+use crate::capability as cap;
+use crate::error;
+use crate::movement::Movement;
+use crate::style::Style;
+use crate::Database;
+
+/// Expand the capability if present, evaluating to whether it was.
+macro_rules! put {
+	($info:expr, $output:expr, $cap:ty $(; $($name:ident($value:expr)),+)?) => {
+		match $info.get::<$cap>() {
+			Some(cap) => {
+				cap.expand()$($(.$name($value.into()))+)?.append($output)?;
+				true
+			}
+
+			None => false,
+		}
+	};
+}
+
+/// An operation with both a parametrized and a single step capability.
+#[derive(Copy, Clone, Debug)]
+enum Step {
+	Forward,
+	Reverse,
+	Insert,
+	Delete,
+}
+
+/// Scrolling of a terminal.
+///
+/// Operations return whether the terminal supports them, the cursor is
+/// restored when a scroll region is used and the terminal can save it,
+/// otherwise its position is unknown afterwards.
+#[derive(Debug)]
+pub struct Scroll<'a> {
+	info: &'a Database,
+	movement: Movement<'a>,
+	lines: u16,
+	region: Option<(u16, u16)>,
+	ndsr: bool,
+	db: bool,
+	da: bool,
+}
+
+impl<'a> Scroll<'a> {
+	/// Create the scrolling for the given terminal.
+	pub fn new(info: &'a Database) -> Self {
+		Scroll {
+			info,
+			movement: Movement::new(info),
+			lines: info.get::<cap::Lines>().map_or(24, |cap| cap.0.max(1) as u16),
+			region: None,
+			ndsr: info.get::<cap::NonDestScrollRegion>().is_some_and(|cap| cap.0),
+			db: info.get::<cap::MemoryBelow>().is_some_and(|cap| cap.0),
+			da: info.get::<cap::MemoryAbove>().is_some_and(|cap| cap.0),
+		}
+	}
+
+	/// Use the given screen height instead of `lines`.
+	pub fn lines(mut self, lines: u16) -> Self {
+		self.lines = lines.max(1);
+		self
+	}
+
+	/// Only scroll the rows between `top` and `bottom` inclusive.
+	pub fn region(mut self, top: u16, bottom: u16) -> Self {
+		self.region = Some((top, bottom));
+		self
+	}
+
+	/// Scroll the contents up by the given number of lines.
+	pub fn scroll_up<W: Write>(&self, output: W, count: u16) -> error::Result<bool> {
+		let (top, bottom) = self.bounds();
+		let count = count.min(bottom - top + 1);
+		let mut sequence = Vec::new();
+
+		let done = count == 0
+			|| attempt(&mut sequence, |sequence| self.scroll(sequence, true, count))?
+			|| attempt(&mut sequence, |sequence| {
+				self.shift(sequence, top, bottom + 1 - count, count)
+			})?;
+
+		self.finish(output, sequence, done)
+	}
+
+	/// Scroll the contents down by the given number of lines.
+	pub fn scroll_down<W: Write>(&self, output: W, count: u16) -> error::Result<bool> {
+		let (top, bottom) = self.bounds();
+		let count = count.min(bottom - top + 1);
+		let mut sequence = Vec::new();
+
+		let done = count == 0
+			|| attempt(&mut sequence, |sequence| self.scroll(sequence, false, count))?
+			|| attempt(&mut sequence, |sequence| {
+				self.shift(sequence, bottom + 1 - count, top, count)
+			})?;
+
+		self.finish(output, sequence, done)
+	}
+
+	/// Insert blank lines at the given row, pushing the following ones down.
+	pub fn insert_lines<W: Write>(&self, output: W, row: u16, count: u16) -> error::Result<bool> {
+		let mut sequence = Vec::new();
+		let done = count == 0
+			|| attempt(&mut sequence, |sequence| {
+				self.lines_at(sequence, row, Step::Insert, count)
+			})? || self.from(row).scroll_down(&mut sequence, count)?;
+
+		self.finish(output, sequence, done)
+	}
+
+	/// Delete lines at the given row, pulling the following ones up.
+	pub fn delete_lines<W: Write>(&self, output: W, row: u16, count: u16) -> error::Result<bool> {
+		let mut sequence = Vec::new();
+		let done = count == 0
+			|| attempt(&mut sequence, |sequence| {
+				self.lines_at(sequence, row, Step::Delete, count)
+			})? || self.from(row).scroll_up(&mut sequence, count)?;
+
+		self.finish(output, sequence, done)
+	}
+
+	/// The scrolling region from the given row to the bottom.
+	fn from(&self, row: u16) -> Scroll<'a> {
+		Scroll::new(self.info).lines(self.lines).region(row, self.lines - 1)
+	}
+
+	/// The region clamped to the screen.
+	fn bounds(&self) -> (u16, u16) {
+		let (top, bottom) = self.region.unwrap_or((0, self.lines - 1));
+		let bottom = bottom.min(self.lines - 1);
+
+		(top.min(bottom), bottom)
+	}
+
+	/// Whether the region is the whole screen.
+	fn full(&self) -> bool {
+		self.bounds() == (0, self.lines - 1)
+	}
+
+	/// Write the sequence if the operation succeeded.
+	fn finish<W: Write>(
+		&self,
+		mut output: W,
+		sequence: Vec<u8>,
+		done: bool,
+	) -> error::Result<bool> {
+		if done {
+			output.write_all(&sequence)?;
+		}
+
+		Ok(done)
+	}
+
+	/// Scroll the region forward or in reverse, directly on the whole screen or
+	/// through the scrolling region.
+	fn scroll(&self, output: &mut Vec<u8>, forward: bool, count: u16) -> error::Result<bool> {
+		let direct = |this: &Self, output: &mut Vec<u8>| {
+			if forward {
+				this.forward(output, count)
+			} else {
+				this.reverse(output, count)
+			}
+		};
+
+		if self.full() && attempt(output, |output| direct(self, output))? {
+			return Ok(true);
+		}
+
+		self.within(output, direct)
+	}
+
+	/// Delete lines at a row and insert as many at another, leaving the rows
+	/// outside the region in place.
+	fn shift(
+		&self,
+		output: &mut Vec<u8>,
+		delete: u16,
+		insert: u16,
+		count: u16,
+	) -> error::Result<bool> {
+		Ok(self.lines_at(output, delete, Step::Delete, count)?
+			&& self.lines_at(output, insert, Step::Insert, count)?)
+	}
+
+	/// Scroll forward at the bottom of the region, clearing the lines the
+	/// terminal may fill with retained contents.
+	fn forward(&self, output: &mut Vec<u8>, count: u16) -> error::Result<bool> {
+		let (_, bottom) = self.bounds();
+
+		if !(self.goto(output, bottom)? && self.repeat(output, Step::Forward, count)?) {
+			return Ok(false);
+		}
+
+		if self.ndsr || (self.db && bottom == self.lines - 1) {
+			self.clear(output, bottom + 1 - count, bottom)?;
+		}
+
+		Ok(true)
+	}
+
+	/// Scroll reverse at the top of the region, clearing the lines the terminal
+	/// may fill with retained contents.
+	fn reverse(&self, output: &mut Vec<u8>, count: u16) -> error::Result<bool> {
+		let (top, _) = self.bounds();
+
+		if !(self.goto(output, top)? && self.repeat(output, Step::Reverse, count)?) {
+			return Ok(false);
+		}
+
+		if self.ndsr || (self.da && top == 0) {
+			self.clear(output, top, top + count - 1)?;
+		}
+
+		Ok(true)
+	}
+
+	/// Run the operation with the scrolling region set, saving and restoring
+	/// the cursor when possible.
+	fn within<F>(&self, output: &mut Vec<u8>, f: F) -> error::Result<bool>
+	where
+		F: FnOnce(&Self, &mut Vec<u8>) -> error::Result<bool>,
+	{
+		let (top, bottom) = self.bounds();
+
+		if self.info.get::<cap::ChangeScrollRegion>().is_none() {
+			return Ok(false);
+		}
+
+		let save = self.info.get::<cap::SaveCursor>().is_some()
+			&& self.info.get::<cap::RestoreCursor>().is_some();
+
+		if save {
+			put!(self.info, output, cap::SaveCursor);
+		}
+
+		put!(self.info, output, cap::ChangeScrollRegion; top(top), bottom(bottom));
+
+		if !f(self, output)? {
+			return Ok(false);
+		}
+
+		put!(self.info, output, cap::ChangeScrollRegion; top(0u16), bottom(self.lines - 1));
+
+		if save {
+			put!(self.info, output, cap::RestoreCursor);
+		}
+
+		Ok(true)
+	}
+
+	/// Insert or delete lines at the given row.
+	fn lines_at(
+		&self,
+		output: &mut Vec<u8>,
+		row: u16,
+		step: Step,
+		count: u16,
+	) -> error::Result<bool> {
+		Ok(self.goto(output, row)? && self.repeat(output, step, count)?)
+	}
+
+	/// Clear the given rows.
+	fn clear(&self, output: &mut Vec<u8>, top: u16, bottom: u16) -> error::Result<()> {
+		if bottom == self.lines - 1 && self.info.get::<cap::ClrEos>().is_some() {
+			self.goto(output, top)?;
+			put!(self.info, output, cap::ClrEos);
+		} else {
+			for row in top..=bottom {
+				self.goto(output, row)?;
+				put!(self.info, output, cap::ClrEol);
+			}
+		}
+
+		Ok(())
+	}
+
+	/// Move the cursor to the start of the given row.
+	fn goto(&self, output: &mut Vec<u8>, row: u16) -> error::Result<bool> {
+		match self.movement.sequence(None, (0, row), &Style::default())? {
+			Some(sequence) => {
+				output.extend(sequence);
+				Ok(true)
+			}
+
+			None => Ok(false),
+		}
+	}
+
+	/// Use the parametrized capability, or repeat the single step one.
+	fn repeat(&self, output: &mut Vec<u8>, step: Step, count: u16) -> error::Result<bool> {
+		let single = match step {
+			Step::Forward => self.info.get::<cap::ScrollForward>().map(|cap| cap.expand().to_vec()),
+			Step::Reverse => self.info.get::<cap::ScrollReverse>().map(|cap| cap.expand().to_vec()),
+			Step::Insert => self.info.get::<cap::InsertLine>().map(|cap| cap.expand().to_vec()),
+			Step::Delete => self.info.get::<cap::DeleteLine>().map(|cap| cap.expand().to_vec()),
+		}
+		.transpose()?;
+
+		if count > 1 || single.is_none() {
+			let done = match step {
+				Step::Forward => put!(self.info, output, cap::ParmIndex; count(count)),
+				Step::Reverse => put!(self.info, output, cap::ParmRindex; count(count)),
+				Step::Insert => put!(self.info, output, cap::ParmInsertLine; count(count)),
+				Step::Delete => put!(self.info, output, cap::ParmDeleteLine; count(count)),
+			};
+
+			if done {
+				return Ok(true);
+			}
+		}
+
+		match single {
+			Some(single) => {
+				output.extend(single.repeat(count as usize));
+				Ok(true)
+			}
+
+			None => Ok(false),
+		}
+	}
+}
+
+/// Run the operation, discarding its output when it fails.
+fn attempt<F>(output: &mut Vec<u8>, f: F) -> error::Result<bool>
+where
+	F: FnOnce(&mut Vec<u8>) -> error::Result<bool>,
+{
+	let length = output.len();
+
+	if f(output)? {
+		Ok(true)
+	} else {
+		output.truncate(length);
+		Ok(false)
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+
+	#[test]
+	fn region() {
+		let info = Database::from_path("tests/xterm-256color").unwrap();
+		let scroll = Scroll::new(&info);
+
+		let mut output = Vec::new();
+		assert!(scroll.scroll_down(&mut output, 1).unwrap());
+		assert_eq!(b"\x1B[H\x1BM".to_vec(), output);
+
+		let mut output = Vec::new();
+		assert!(scroll.insert_lines(&mut output, 3, 2).unwrap());
+		assert_eq!(b"\x1B[4;1H\x1B[2L".to_vec(), output);
+
+		let mut output = Vec::new();
+		assert!(scroll.region(2, 100).scroll_up(&mut output, 1).unwrap());
+		assert_eq!(b"\x1B7\x1B[3;24r\x1B[24;1H\n\x1B[1;24r\x1B8".to_vec(), output);
+	}
+
+	#[test]
+	fn fallback() {
+		let mut info = Database::new();
+		info.name("test")
+			.raw("lines", 10)
+			.raw("db", ())
+			.raw("cup", "\x1B[%i%p1%d;%p2%dH")
+			.raw("ind", "\n")
+			.raw("il1", "\x1B[L")
+			.raw("dl1", "\x1B[M")
+			.raw("ed", "\x1B[J");
+		let info = info.build().unwrap();
+		let scroll = Scroll::new(&info);
+
+		let mut output = Vec::new();
+		assert!(scroll.scroll_up(&mut output, 2).unwrap());
+		assert_eq!(b"\x1B[10;1H\n\n\x1B[9;1H\x1B[J".to_vec(), output);
+
+		let mut output = Vec::new();
+		assert!(scroll.scroll_down(&mut output, 1).unwrap());
+		assert_eq!(b"\x1B[10;1H\x1B[M\x1B[1;1H\x1B[L".to_vec(), output);
+
+		let mut output = Vec::new();
+		assert!(scroll.region(1, 3).scroll_up(&mut output, 1).unwrap());
+		assert_eq!(b"\x1B[2;1H\x1B[M\x1B[4;1H\x1B[L".to_vec(), output);
+
+		let mut info = Database::new();
+		info.name("test").raw("ind", "\n");
+		let info = info.build().unwrap();
+
+		let mut output = Vec::new();
+		assert!(!Scroll::new(&info).scroll_up(&mut output, 1).unwrap());
+		assert!(output.is_empty());
+	}
+}
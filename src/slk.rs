@@ -0,0 +1,247 @@
+//            DO WHAT THE FUCK YOU WANT TO PUBLIC LICENSE
+//                    Version 2, December 2004
+//
+// Copyleft (ↄ) meh. <meh@schizofreni.co> | http://meh.schizofreni.co
+//
+// Everyone is permitted to copy and distribute verbatim or modified
+// copies of this license document, and changing it is allowed as long
+// as the name is changed.
+//
+//            DO WHAT THE FUCK YOU WANT TO PUBLIC LICENSE
+//   TERMS AND CONDITIONS FOR COPYING, DISTRIBUTION AND MODIFICATION
+//
+//  0. You just DO WHAT THE FUCK YOU WANT TO.
+
+//! Soft label keys.
+//!
+//! ## Example
+//!
+//! ```
+//! use terminfo::Database;
+//! use terminfo::slk::{Program, Slk};
+//!
+//! let mut info = Database::new();
+//! info.name("test")
+//!     .raw("nlab", 8)
+//!     .raw("lw", 4)
+//!     .raw("pln", "\x1B[%p1%dL%p2%s")
+//!     .raw("pfx", "\x1B[%p1%dP%p2%s");
+//! let info = info.build().unwrap();
+//! let slk  = Slk::new(&info);
+//!
+//! let mut output = Vec::new();
+//! slk.label(&mut output, 1, "Help me").unwrap();
+//! slk.program(&mut output, 1, "help\r", Program::Xmit).unwrap();
+//! assert_eq!(b"\x1B[1LHelp\x1B[1Phelp\r".to_vec(), output);
+//! ```
+
+use std::io::Write;
+
+use crate::capability::{self as cap, Capability, Value};
+use crate::error;
+use crate::Database;
+
+/// Expand the capability to the output if present, evaluating to whether it
+/// was.
+macro_rules! put {
+	($info:expr, $output:expr, $cap:ty $(; $($name:ident($value:expr)),+)?) => {
+		match $info.get::<$cap>() {
+			Some(cap) => {
+				cap.expand()$($(.$name($value))+)?.to($output)?;
+				true
+			}
+
+			None => false,
+		}
+	};
+}
+
+/// How a programmed function key sends its string.
+#[derive(Eq, PartialEq, Copy, Clone, Hash, Debug)]
+pub enum Program {
+	/// As if typed by the user, with `pkey_key`.
+	Key,
+
+	/// Executed locally by the terminal, with `pkey_local`.
+	Local,
+
+	/// Transmitted to the computer, with `pkey_xmit`.
+	Xmit,
+}
+
+impl Program {
+	/// The name of the capability.
+	pub fn capability(self) -> &'static str {
+		match self {
+			Program::Key => "pkey_key",
+
+			Program::Local => "pkey_local",
+
+			Program::Xmit => "pkey_xmit",
+		}
+	}
+}
+
+/// The soft label keys of a terminal.
+///
+/// Operations return whether the terminal supports them.
+#[derive(Clone, Debug)]
+pub struct Slk<'a> {
+	info: &'a Database,
+	count: u16,
+	width: usize,
+	height: u16,
+}
+
+impl<'a> Slk<'a> {
+	/// Load the soft label keys of the given terminal.
+	pub fn new(info: &'a Database) -> Self {
+		Slk {
+			info,
+			count: info.get::<cap::NumLabels>().map_or(0, |cap| cap.0.max(0) as u16),
+			width: info.get::<cap::LabelWidth>().map_or(8, |cap| cap.0.max(0) as usize),
+			height: info.get::<cap::LabelHeight>().map_or(1, |cap| cap.0.max(0) as u16),
+		}
+	}
+
+	/// The number of labels.
+	pub fn count(&self) -> u16 {
+		self.count
+	}
+
+	/// The number of columns in a label.
+	pub fn width(&self) -> usize {
+		self.width
+	}
+
+	/// The number of rows in a label.
+	pub fn height(&self) -> u16 {
+		self.height
+	}
+
+	/// The format of the labels, if any.
+	pub fn format(&self) -> Option<&'a [u8]> {
+		self.string::<cap::LabelFormat>()
+	}
+
+	/// The label printed on the given function key, for keys `0` to `10`.
+	pub fn default_label(&self, key: u8) -> Option<&'a [u8]> {
+		match key {
+			0 => self.string::<cap::LabF0>(),
+			1 => self.string::<cap::LabF1>(),
+			2 => self.string::<cap::LabF2>(),
+			3 => self.string::<cap::LabF3>(),
+			4 => self.string::<cap::LabF4>(),
+			5 => self.string::<cap::LabF5>(),
+			6 => self.string::<cap::LabF6>(),
+			7 => self.string::<cap::LabF7>(),
+			8 => self.string::<cap::LabF8>(),
+			9 => self.string::<cap::LabF9>(),
+			10 => self.string::<cap::LabF10>(),
+			_ => None,
+		}
+	}
+
+	/// Set the text of a label, starting from `1`.
+	///
+	/// The text is truncated or padded with spaces to the label width.
+	pub fn label<W: Write>(&self, output: W, index: u16, text: &str) -> error::Result<bool> {
+		if index == 0 || index > self.count {
+			return Ok(false);
+		}
+
+		let text = format!("{:1$.1$}", text, self.width);
+		Ok(put!(self.info, output, cap::PlabNorm; label(index.into()), text(text)))
+	}
+
+	/// Program a function key to send the given string.
+	///
+	/// Function keys are not limited by `num_labels` like labels are, so the
+	/// key is passed to the terminal unchecked.
+	pub fn program<W: Write, T: AsRef<[u8]>>(
+		&self,
+		output: W,
+		key: u16,
+		string: T,
+		kind: Program,
+	) -> error::Result<bool> {
+		let (key, command) = (key.into(), string.as_ref().to_vec());
+
+		Ok(match kind {
+			Program::Key => put!(self.info, output, cap::PKeyKey; key(key), command(command)),
+			Program::Local => put!(self.info, output, cap::PKeyLocal; key(key), command(command)),
+			Program::Xmit => put!(self.info, output, cap::PKeyXmit; key(key), command(command)),
+		})
+	}
+
+	/// Program a function key to send the given string and show the given
+	/// label, with `pkey_plab`, starting from `1`.
+	pub fn program_with_label<W: Write, T: AsRef<[u8]>>(
+		&self,
+		output: W,
+		key: u16,
+		string: T,
+		label: &str,
+	) -> error::Result<bool> {
+		if key == 0 || key > self.count {
+			return Ok(false);
+		}
+
+		let label = format!("{:1$.1$}", label, self.width);
+		let command = string.as_ref().to_vec();
+		Ok(put!(self.info, output, cap::PkeyPlab; key(key.into()), command(command), label(label)))
+	}
+
+	/// Show the labels.
+	pub fn show<W: Write>(&self, output: W) -> error::Result<bool> {
+		Ok(put!(self.info, output, cap::LabelOn))
+	}
+
+	/// Hide the labels.
+	pub fn hide<W: Write>(&self, output: W) -> error::Result<bool> {
+		Ok(put!(self.info, output, cap::LabelOff))
+	}
+
+	/// Get a string capability, unexpanded.
+	fn string<C: Capability<'a>>(&self) -> Option<&'a [u8]> {
+		match self.info.raw(C::name()) {
+			Some(Value::String(value)) => Some(value),
+			_ => None,
+		}
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+
+	#[test]
+	fn labels() {
+		let mut info = Database::new();
+		info.name("test")
+			.raw("nlab", 2)
+			.raw("lw", 3)
+			.raw("lf0", "F0")
+			.raw("pln", "<%p1%d:%p2%s>")
+			.raw("pfx", "[%p1%d:%p2%s]")
+			.raw("pfxl", "<%p1%d:%p2%s:%p3%s>")
+			.raw("smln", "+")
+			.raw("rmln", "-");
+		let info = info.build().unwrap();
+		let slk = Slk::new(&info);
+
+		assert_eq!(Some(&b"F0"[..]), slk.default_label(0));
+		assert_eq!(None, slk.default_label(1));
+
+		let mut output = Vec::new();
+		assert!(slk.show(&mut output).unwrap());
+		assert!(slk.label(&mut output, 2, "a").unwrap());
+		assert!(!slk.label(&mut output, 3, "a").unwrap());
+		assert!(slk.program_with_label(&mut output, 1, "x", "long").unwrap());
+		assert!(!slk.program_with_label(&mut output, 3, "x", "long").unwrap());
+		assert!(!slk.program(&mut output, 1, "x", Program::Local).unwrap());
+		assert!(slk.program(&mut output, 3, "y", Program::Xmit).unwrap());
+		assert!(slk.hide(&mut output).unwrap());
+		assert_eq!(b"+<2:a  ><1:x:lon>[3:y]-".to_vec(), output);
+	}
+}
@@ -0,0 +1,514 @@
+//            DO WHAT THE FUCK YOU WANT TO PUBLIC LICENSE
+//                    Version 2, December 2004
+//
+// Copyleft (ↄ) meh. <meh@schizofreni.co> | http://meh.schizofreni.co
+//
+// Everyone is permitted to copy and distribute verbatim or modified
+// copies of this license document, and changing it is allowed as long
+// as the name is changed.
+//
+//            DO WHAT THE FUCK YOU WANT TO PUBLIC LICENSE
+//   TERMS AND CONDITIONS FOR COPYING, DISTRIBUTION AND MODIFICATION
+//
+//  0. You just DO WHAT THE FUCK YOU WANT TO.
+
+//! Text styles and the transitions between them.
+//!
+//! ## Example
+//!
+//! ```
+//! use terminfo::Database;
+//! use terminfo::color::Color;
+//! use terminfo::style::{Attributes, Renderer, Style};
+//!
+//! let info     = Database::from_path("tests/xterm-256color").unwrap();
+//! let renderer = Renderer::new(&info);
+//!
+//! let plain = Style::default();
+//! let bold  = Style::default().attributes(Attributes::BOLD);
+//! let red   = bold.foreground(Color::Ansi(1));
+//!
+//! let mut output = Vec::new();
+//! renderer.transition(&mut output, &plain, &bold).unwrap();
+//! renderer.transition(&mut output, &bold, &red).unwrap();
+//! renderer.transition(&mut output, &red, &plain).unwrap();
+//!
+//! assert_eq!(b"\x1B[1m\x1B[31m\x1B(B\x1B[m".to_vec(), output);
+//! ```
+
+use std::io::Write;
+use std::ops::{BitAnd, BitOr, BitOrAssign, Not};
+
+use crate::capability as cap;
+use crate::color::{Color, Colors};
+use crate::error;
+use crate::expand::Scratch;
+use crate::Database;
+
+/// Text attributes, with the same bits used by `no_color_video`.
+#[derive(Eq, PartialEq, Copy, Clone, Hash, Default, Debug)]
+pub struct Attributes(u16);
+
+impl Attributes {
+	/// No attributes.
+	pub const NONE: Attributes = Attributes(0);
+
+	/// Standout mode.
+	pub const STANDOUT: Attributes = Attributes(1 << 0);
+
+	/// Underlined text.
+	pub const UNDERLINE: Attributes = Attributes(1 << 1);
+
+	/// Reversed colors.
+	pub const REVERSE: Attributes = Attributes(1 << 2);
+
+	/// Blinking text.
+	pub const BLINK: Attributes = Attributes(1 << 3);
+
+	/// Half-bright text.
+	pub const DIM: Attributes = Attributes(1 << 4);
+
+	/// Bold text.
+	pub const BOLD: Attributes = Attributes(1 << 5);
+
+	/// Invisible text.
+	pub const INVISIBLE: Attributes = Attributes(1 << 6);
+
+	/// Protected text.
+	pub const PROTECTED: Attributes = Attributes(1 << 7);
+
+	/// Alternate character set.
+	pub const ALT_CHARSET: Attributes = Attributes(1 << 8);
+
+	/// Italic text.
+	pub const ITALIC: Attributes = Attributes(1 << 15);
+
+	/// Create the attributes from their bits.
+	pub fn from_bits(bits: u16) -> Self {
+		Attributes(bits)
+	}
+
+	/// The bits of the attributes.
+	pub fn bits(self) -> u16 {
+		self.0
+	}
+
+	/// Whether all the given attributes are present.
+	pub fn contains(self, other: Attributes) -> bool {
+		self.0 & other.0 == other.0
+	}
+
+	/// Whether no attributes are present.
+	pub fn is_empty(self) -> bool {
+		self.0 == 0
+	}
+}
+
+impl BitOr for Attributes {
+	type Output = Self;
+
+	fn bitor(self, other: Self) -> Self {
+		Attributes(self.0 | other.0)
+	}
+}
+
+impl BitOrAssign for Attributes {
+	fn bitor_assign(&mut self, other: Self) {
+		self.0 |= other.0;
+	}
+}
+
+impl BitAnd for Attributes {
+	type Output = Self;
+
+	fn bitand(self, other: Self) -> Self {
+		Attributes(self.0 & other.0)
+	}
+}
+
+impl Not for Attributes {
+	type Output = Self;
+
+	fn not(self) -> Self {
+		Attributes(!self.0)
+	}
+}
+
+/// Every attribute, in the order they're entered.
+const MODES: &[Attributes] = &[
+	Attributes::STANDOUT,
+	Attributes::UNDERLINE,
+	Attributes::REVERSE,
+	Attributes::BLINK,
+	Attributes::DIM,
+	Attributes::BOLD,
+	Attributes::INVISIBLE,
+	Attributes::PROTECTED,
+	Attributes::ALT_CHARSET,
+	Attributes::ITALIC,
+];
+
+/// Expand the capability if present, evaluating to whether it was.
+macro_rules! put {
+	($info:expr, $output:expr, $scratch:expr, $cap:ty) => {
+		match $info.get::<$cap>() {
+			Some(cap) => {
+				cap.expand().scratch(&mut *$scratch).append(&mut *$output)?;
+				true
+			}
+
+			None => false,
+		}
+	};
+}
+
+/// A text style.
+#[derive(Eq, PartialEq, Copy, Clone, Hash, Default, Debug)]
+pub struct Style {
+	/// The attributes.
+	pub attributes: Attributes,
+
+	/// The foreground color, `None` for the default.
+	pub foreground: Option<Color>,
+
+	/// The background color, `None` for the default.
+	pub background: Option<Color>,
+}
+
+impl Style {
+	/// Add the given attributes.
+	pub fn attributes(mut self, attributes: Attributes) -> Self {
+		self.attributes |= attributes;
+		self
+	}
+
+	/// Set the foreground color.
+	pub fn foreground(mut self, color: Color) -> Self {
+		self.foreground = Some(color);
+		self
+	}
+
+	/// Set the background color.
+	pub fn background(mut self, color: Color) -> Self {
+		self.background = Some(color);
+		self
+	}
+}
+
+/// Renderer of style transitions for a terminal.
+///
+/// Transitions are done either by resetting with `exit_attribute_mode`, by
+/// setting everything with `set_attributes`, or by entering and exiting the
+/// changed attributes, whichever is shortest.
+///
+/// Attributes the terminal can't combine with colors, as per
+/// `no_color_video`, are dropped when colors are used, and terminals with
+/// `magic_cookie_glitch` only get colors since attributes would take up space
+/// on screen.
+#[derive(Debug)]
+pub struct Renderer<'a> {
+	info: &'a Database,
+	colors: Colors<'a>,
+	ncv: Attributes,
+	msgr: bool,
+	xmc: bool,
+}
+
+impl<'a> Renderer<'a> {
+	/// Create a renderer for the given terminal.
+	pub fn new(info: &'a Database) -> Self {
+		Renderer {
+			info,
+			colors: Colors::new(info),
+			ncv: Attributes(info.get::<cap::NoColorVideo>().map_or(0, |cap| cap.0 as u16)),
+			msgr: info.get::<cap::MoveStandoutMode>().is_some_and(|cap| cap.0),
+			xmc: info.get::<cap::MagicCookieGlitch>().is_some_and(|cap| cap.0 > 0),
+		}
+	}
+
+	/// Use the given colors instead of the detected ones.
+	pub fn with_colors(mut self, colors: Colors<'a>) -> Self {
+		self.colors = colors;
+		self
+	}
+
+	/// The style the terminal will actually display for the given one.
+	pub fn effective(&self, style: &Style) -> Style {
+		let mut style = *style;
+
+		if self.xmc {
+			style.attributes = Attributes::NONE;
+		}
+
+		if style.foreground.is_some() || style.background.is_some() {
+			style.attributes = style.attributes & !self.ncv;
+		}
+
+		style
+	}
+
+	/// Write the shortest sequence to go from one style to another.
+	pub fn transition<W: Write>(
+		&self,
+		mut output: W,
+		from: &Style,
+		to: &Style,
+	) -> error::Result<()> {
+		let from = self.effective(from);
+		let to = self.effective(to);
+
+		if from == to {
+			return Ok(());
+		}
+
+		let mut scratch = Scratch::default();
+		let candidates = [
+			self.reset(&mut scratch, &to)?,
+			self.set(&mut scratch, &to)?,
+			self.change(&mut scratch, &from, &to)?,
+		];
+		let shortest = candidates.iter().flatten().min_by_key(|sequence| sequence.len());
+
+		if let Some(sequence) = shortest {
+			output.write_all(sequence)?;
+		}
+
+		Ok(())
+	}
+
+	/// Write what's needed before moving the cursor, returning the style in
+	/// effect afterwards.
+	///
+	/// Without `move_standout_mode` attributes must be reset before moving.
+	pub fn before_move<W: Write>(&self, output: W, style: &Style) -> error::Result<Style> {
+		let style = self.effective(style);
+
+		if self.msgr || style.attributes.is_empty() {
+			return Ok(style);
+		}
+
+		let reset = Style::default();
+		self.transition(output, &style, &reset)?;
+
+		Ok(reset)
+	}
+
+	/// Enter the given attribute, returning whether it's supported.
+	fn enter_mode(
+		&self,
+		output: &mut Vec<u8>,
+		scratch: &mut Scratch,
+		attribute: Attributes,
+	) -> error::Result<bool> {
+		Ok(match attribute {
+			Attributes::STANDOUT => put!(self.info, output, scratch, cap::EnterStandoutMode),
+			Attributes::UNDERLINE => put!(self.info, output, scratch, cap::EnterUnderlineMode),
+			Attributes::REVERSE => put!(self.info, output, scratch, cap::EnterReverseMode),
+			Attributes::BLINK => put!(self.info, output, scratch, cap::EnterBlinkMode),
+			Attributes::DIM => put!(self.info, output, scratch, cap::EnterDimMode),
+			Attributes::BOLD => put!(self.info, output, scratch, cap::EnterBoldMode),
+			Attributes::INVISIBLE => put!(self.info, output, scratch, cap::EnterSecureMode),
+			Attributes::PROTECTED => put!(self.info, output, scratch, cap::EnterProtectedMode),
+			Attributes::ALT_CHARSET => put!(self.info, output, scratch, cap::EnterAltCharsetMode),
+			Attributes::ITALIC => put!(self.info, output, scratch, cap::EnterItalicsMode),
+			_ => false,
+		})
+	}
+
+	/// Exit the given attribute, returning whether it's supported.
+	fn exit_mode(
+		&self,
+		output: &mut Vec<u8>,
+		scratch: &mut Scratch,
+		attribute: Attributes,
+	) -> error::Result<bool> {
+		Ok(match attribute {
+			Attributes::STANDOUT => put!(self.info, output, scratch, cap::ExitStandoutMode),
+			Attributes::UNDERLINE => put!(self.info, output, scratch, cap::ExitUnderlineMode),
+			Attributes::ALT_CHARSET => put!(self.info, output, scratch, cap::ExitAltCharsetMode),
+			Attributes::ITALIC => put!(self.info, output, scratch, cap::ExitItalicsMode),
+			_ => false,
+		})
+	}
+
+	/// Enter all the given attributes.
+	fn enter(
+		&self,
+		output: &mut Vec<u8>,
+		scratch: &mut Scratch,
+		attributes: Attributes,
+	) -> error::Result<()> {
+		for &attribute in MODES {
+			if attributes.contains(attribute) {
+				self.enter_mode(output, scratch, attribute)?;
+			}
+		}
+
+		Ok(())
+	}
+
+	/// Set the colors of the style from the default ones.
+	fn colors(&self, output: &mut Vec<u8>, style: &Style) -> error::Result<()> {
+		if let Some(color) = style.foreground {
+			self.colors.foreground(&mut *output, color)?;
+		}
+
+		if let Some(color) = style.background {
+			self.colors.background(&mut *output, color)?;
+		}
+
+		Ok(())
+	}
+
+	/// Reset everything and set the style.
+	fn reset(&self, scratch: &mut Scratch, to: &Style) -> error::Result<Option<Vec<u8>>> {
+		let mut output = Vec::new();
+
+		if !put!(self.info, &mut output, scratch, cap::ExitAttributeMode) {
+			return Ok(None);
+		}
+
+		self.enter(&mut output, scratch, to.attributes)?;
+		self.colors(&mut output, to)?;
+
+		Ok(Some(output))
+	}
+
+	/// Set all the attributes at once.
+	fn set(&self, scratch: &mut Scratch, to: &Style) -> error::Result<Option<Vec<u8>>> {
+		let mut output = Vec::new();
+		let cap = match self.info.get::<cap::SetAttributes>() {
+			Some(cap) => cap,
+			None => return Ok(None),
+		};
+
+		let has = |attribute| to.attributes.contains(attribute);
+		cap.expand()
+			.parameters(
+				has(Attributes::STANDOUT),
+				has(Attributes::UNDERLINE),
+				has(Attributes::REVERSE),
+				has(Attributes::BLINK),
+				has(Attributes::DIM),
+				has(Attributes::BOLD),
+				has(Attributes::INVISIBLE),
+				has(Attributes::PROTECTED),
+				has(Attributes::ALT_CHARSET),
+			)
+			.scratch(&mut *scratch)
+			.append(&mut output)?;
+
+		// Italics are not part of `set_attributes`.
+		self.enter(&mut output, scratch, to.attributes & Attributes::ITALIC)?;
+		self.colors(&mut output, to)?;
+
+		Ok(Some(output))
+	}
+
+	/// Exit and enter only the changed attributes and colors.
+	fn change(
+		&self,
+		scratch: &mut Scratch,
+		from: &Style,
+		to: &Style,
+	) -> error::Result<Option<Vec<u8>>> {
+		let mut output = Vec::new();
+
+		for &attribute in MODES {
+			if from.attributes.contains(attribute)
+				&& !to.attributes.contains(attribute)
+				&& !self.exit_mode(&mut output, scratch, attribute)?
+			{
+				return Ok(None);
+			}
+		}
+
+		self.enter(&mut output, scratch, to.attributes & !from.attributes)?;
+
+		let reset = (from.foreground.is_some() && to.foreground.is_none())
+			|| (from.background.is_some() && to.background.is_none());
+
+		if reset {
+			if !put!(self.info, &mut output, scratch, cap::OrigPair) {
+				return Ok(None);
+			}
+
+			self.colors(&mut output, to)?;
+		} else {
+			if from.foreground != to.foreground {
+				self.colors.foreground(&mut output, to.foreground.unwrap())?;
+			}
+
+			if from.background != to.background {
+				self.colors.background(&mut output, to.background.unwrap())?;
+			}
+		}
+
+		Ok(Some(output))
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+
+	fn transition(renderer: &Renderer, from: &Style, to: &Style) -> Vec<u8> {
+		let mut output = Vec::new();
+		renderer.transition(&mut output, from, to).unwrap();
+		output
+	}
+
+	#[test]
+	fn shortest() {
+		let info = Database::from_path("tests/xterm-256color").unwrap();
+		let renderer = Renderer::new(&info);
+		let plain = Style::default();
+		let underline = plain.attributes(Attributes::UNDERLINE);
+		let both = underline.attributes(Attributes::BOLD);
+
+		assert_eq!(b"".to_vec(), transition(&renderer, &both, &both));
+		assert_eq!(b"\x1B[4m\x1B[1m".to_vec(), transition(&renderer, &plain, &both));
+		assert_eq!(b"\x1B[24m".to_vec(), transition(&renderer, &underline, &plain));
+		assert_eq!(b"\x1B(B\x1B[0;4m".to_vec(), transition(&renderer, &both, &underline));
+		assert_eq!(
+			b"\x1B(B\x1B[m\x1B[32m".to_vec(),
+			transition(
+				&renderer,
+				&plain.foreground(Color::Ansi(1)).background(Color::Ansi(2)),
+				&plain.foreground(Color::Ansi(2))
+			)
+		);
+	}
+
+	#[test]
+	fn restrictions() {
+		let mut info = Database::new();
+		info.name("test")
+			.raw("colors", 8)
+			.raw("ncv", 2)
+			.raw("setaf", "\x1B[3%p1%dm")
+			.raw("smul", "\x1B[4m")
+			.raw("bold", "\x1B[1m")
+			.raw("sgr0", "\x1B[m");
+		let info = info.build().unwrap();
+		let renderer = Renderer::new(&info);
+
+		let style = Style::default().attributes(Attributes::UNDERLINE | Attributes::BOLD);
+		assert_eq!(b"\x1B[4m\x1B[1m".to_vec(), transition(&renderer, &Style::default(), &style));
+		assert_eq!(
+			b"\x1B[m\x1B[1m\x1B[31m".to_vec(),
+			transition(&renderer, &style, &style.foreground(Color::Ansi(1)))
+		);
+
+		let mut output = Vec::new();
+		assert_eq!(Style::default(), renderer.before_move(&mut output, &style).unwrap());
+		assert_eq!(b"\x1B[m".to_vec(), output);
+
+		let mut info = Database::new();
+		info.name("test").raw("xmc", 1).raw("smso", "\x1B[7m");
+		let info = info.build().unwrap();
+		let renderer = Renderer::new(&info);
+		let style = Style::default().attributes(Attributes::STANDOUT);
+		assert_eq!(b"".to_vec(), transition(&renderer, &Style::default(), &style));
+	}
+}
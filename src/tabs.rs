@@ -0,0 +1,215 @@
+//            DO WHAT THE FUCK YOU WANT TO PUBLIC LICENSE
+//                    Version 2, December 2004
+//
+// Copyleft (ↄ) meh. <meh@schizofreni.co> | http://meh.schizofreni.co
+//
+// Everyone is permitted to copy and distribute verbatim or modified
+// copies of this license document, and changing it is allowed as long
+// as the name is changed.
+//
+//            DO WHAT THE FUCK YOU WANT TO PUBLIC LICENSE
+//   TERMS AND CONDITIONS FOR COPYING, DISTRIBUTION AND MODIFICATION
+//
+//  0. You just DO WHAT THE FUCK YOU WANT TO.
+
+//! Tab stop management.
+//!
+//! ## Example
+//!
+//! ```
+//! use terminfo::Database;
+//! use terminfo::tabs::Tabs;
+//!
+//! let info     = Database::from_path("tests/xterm-256color").unwrap();
+//! let mut tabs = Tabs::new(&info);
+//!
+//! assert_eq!(Some(b"\t\t  ".to_vec()), tabs.reach(3, 18));
+//!
+//! let mut output = Vec::new();
+//! tabs.program(&mut output, [4, 10]).unwrap();
+//! assert_eq!(b"\r\x1B[3g\x1B[5G\x1BH\x1B[11G\x1BH\r".to_vec(), output);
+//! assert_eq!(Some(b"\t\t".to_vec()), tabs.reach(0, 10));
+//! ```
+
+use std::collections::BTreeSet;
+use std::io::Write;
+
+use crate::capability as cap;
+use crate::error;
+use crate::expand::Scratch;
+use crate::Database;
+
+/// The tab stops of a terminal.
+///
+/// Tab stops start every `init_tabs` columns, and hardware tabs are not used
+/// when the terminal has `dest_tabs_magic_smso`.
+#[derive(Clone, Debug)]
+pub struct Tabs<'a> {
+	info: &'a Database,
+	stops: BTreeSet<u16>,
+	hardware: bool,
+}
+
+impl<'a> Tabs<'a> {
+	/// Load the tab stops of the given terminal.
+	pub fn new(info: &'a Database) -> Self {
+		let columns = info.get::<cap::Columns>().map_or(80, |cap| cap.0.max(0) as u16);
+		let width = info.get::<cap::InitTabs>().map_or(8, |cap| cap.0.max(0) as usize);
+		let tab = info.get::<cap::Tab>().is_some()
+			|| info.get::<cap::HasHardwareTabs>().is_some_and(|cap| cap.0);
+
+		Tabs {
+			info,
+			stops: if width > 0 {
+				(width as u16..columns).step_by(width).collect()
+			} else {
+				BTreeSet::new()
+			},
+			hardware: tab && !info.get::<cap::DestTabsMagicSmso>().is_some_and(|cap| cap.0),
+		}
+	}
+
+	/// Whether hardware tabs can be trusted.
+	pub fn hardware(&self) -> bool {
+		self.hardware
+	}
+
+	/// The current tab stops.
+	pub fn stops(&self) -> impl Iterator<Item = u16> + '_ {
+		self.stops.iter().copied()
+	}
+
+	/// The tab stop after the given column, if any.
+	pub fn next(&self, column: u16) -> Option<u16> {
+		self.stops.range(column.checked_add(1)?..).next().copied()
+	}
+
+	/// The tab stop before the given column, or the first column.
+	pub fn previous(&self, column: u16) -> u16 {
+		self.stops.range(..column).next_back().copied().unwrap_or(0)
+	}
+
+	/// Program the given tab stops, returning whether the terminal supports
+	/// it.
+	///
+	/// The cursor is moved to the stops and left at the start of the line.
+	pub fn program<W: Write, I: IntoIterator<Item = u16>>(
+		&mut self,
+		mut output: W,
+		stops: I,
+	) -> error::Result<bool> {
+		let (clear, set) =
+			match (self.info.get::<cap::ClearAllTabs>(), self.info.get::<cap::SetTab>()) {
+				(Some(clear), Some(set)) => (clear, set),
+				_ => return Ok(false),
+			};
+
+		let address = self.info.get::<cap::ColumnAddress>();
+		let stops = stops.into_iter().filter(|&stop| stop > 0).collect::<BTreeSet<_>>();
+		let mut scratch = Scratch::default();
+		let mut column = 0;
+
+		output.write_all(b"\r")?;
+		clear.expand().scratch(&mut scratch).to(&mut output)?;
+
+		for &stop in &stops {
+			if let Some(address) = &address {
+				address.expand().x(stop.into()).scratch(&mut scratch).to(&mut output)?;
+			} else {
+				output.write_all(&b" ".repeat((stop - column) as usize))?;
+			}
+
+			set.expand().scratch(&mut scratch).to(&mut output)?;
+			column = stop;
+		}
+
+		output.write_all(b"\r")?;
+		self.stops = stops;
+
+		Ok(true)
+	}
+
+	/// Find the `tab` and `back_tab` emissions to reach a column, followed by
+	/// spaces for the remainder.
+	///
+	/// Only spaces are used without trustworthy hardware tabs, and `None` is
+	/// returned when the column cannot be reached.
+	pub fn reach(&self, from: u16, to: u16) -> Option<Vec<u8>> {
+		let mut output = Vec::new();
+		let mut column = from;
+
+		if to < from {
+			let back = self.info.get::<cap::BackTab>().filter(|_| self.hardware)?;
+
+			while column > to {
+				column = self.previous(column);
+				output.extend_from_slice(back.as_ref());
+			}
+		} else if self.hardware {
+			let tab = self.info.get::<cap::Tab>();
+			let tab = tab.as_ref().map_or(&b"\t"[..], |tab| tab.as_ref());
+
+			while let Some(stop) = self.next(column).filter(|&stop| stop <= to) {
+				column = stop;
+				output.extend_from_slice(tab);
+			}
+		}
+
+		output.extend(b" ".repeat((to - column) as usize));
+		Some(output)
+	}
+
+	/// Write the emissions to reach a column, returning whether it can be
+	/// reached.
+	pub fn write<W: Write>(&self, mut output: W, from: u16, to: u16) -> error::Result<bool> {
+		match self.reach(from, to) {
+			Some(sequence) => {
+				output.write_all(&sequence)?;
+				Ok(true)
+			}
+
+			None => Ok(false),
+		}
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+
+	#[test]
+	fn reach() {
+		let info = Database::from_path("tests/xterm-256color").unwrap();
+		let tabs = Tabs::new(&info);
+
+		assert!(tabs.hardware());
+		assert_eq!(Some(16), tabs.next(8));
+		assert_eq!(None, tabs.next(u16::MAX));
+		assert_eq!(8, tabs.previous(16));
+		assert_eq!(Some(b"\t".to_vec()), tabs.reach(0, 8));
+		assert_eq!(Some(b"\x1B[Z\x1B[Z ".to_vec()), tabs.reach(20, 9));
+		assert_eq!(Some(b"\x1B[Z".to_vec()), tabs.reach(5, 0));
+	}
+
+	#[test]
+	fn untrusted() {
+		let mut info = Database::new();
+		info.name("test")
+			.raw("xt", ())
+			.raw("ht", "\t")
+			.raw("cbt", "\x1B[Z")
+			.raw("tbc", "\x1B[3g")
+			.raw("hts", "\x1BH");
+		let info = info.build().unwrap();
+		let mut tabs = Tabs::new(&info);
+
+		assert!(!tabs.hardware());
+		assert_eq!(Some(b"     ".to_vec()), tabs.reach(3, 8));
+		assert_eq!(None, tabs.reach(8, 3));
+
+		let mut output = Vec::new();
+		assert!(tabs.program(&mut output, [6, 3, 0]).unwrap());
+		assert_eq!(b"\r\x1B[3g   \x1BH   \x1BH\r".to_vec(), output);
+		assert_eq!(vec![3, 6], tabs.stops().collect::<Vec<_>>());
+	}
+}
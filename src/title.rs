@@ -0,0 +1,160 @@
+//            DO WHAT THE FUCK YOU WANT TO PUBLIC LICENSE
+//                    Version 2, December 2004
+//
+// Copyleft (ↄ) meh. <meh@schizofreni.co> | http://meh.schizofreni.co
+//
+// Everyone is permitted to copy and distribute verbatim or modified
+// copies of this license document, and changing it is allowed as long
+// as the name is changed.
+//
+//            DO WHAT THE FUCK YOU WANT TO PUBLIC LICENSE
+//   TERMS AND CONDITIONS FOR COPYING, DISTRIBUTION AND MODIFICATION
+//
+//  0. You just DO WHAT THE FUCK YOU WANT TO.
+
+//! Window title and status line.
+//!
+//! ## Example
+//!
+//! ```
+//! use terminfo::Database;
+//! use terminfo::title::Title;
+//!
+//! let info = Database::from_path("tests/xterm-256color").unwrap();
+//!
+//! let mut output = Vec::new();
+//! Title::new(&info).unwrap().set_title(&mut output, "hello\x1B world").unwrap();
+//! assert_eq!(b"\x1B]0;hello world\x07".to_vec(), output);
+//! ```
+
+use std::io::Write;
+
+use crate::capability as cap;
+use crate::error;
+use crate::Database;
+
+/// The window title or status line of a terminal.
+///
+/// The status line is used when the terminal has one, otherwise the `TS` and
+/// `FS` extended strings, otherwise the xterm title sequence when the terminal
+/// has the `XT` extended boolean.
+#[derive(Eq, PartialEq, Clone, Debug)]
+pub struct Title {
+	to: Option<Vec<u8>>,
+	from: Vec<u8>,
+	escapes: bool,
+	width: Option<usize>,
+}
+
+impl Title {
+	/// Find how to set the title of the given terminal.
+	pub fn new(info: &Database) -> error::Result<Self> {
+		let status = info.get::<cap::HasStatusLine>().is_some_and(|cap| cap.0);
+		let (to, from) = match info.get::<cap::ToStatusLine>().filter(|_| status) {
+			Some(to) => (
+				Some(to.expand().x(0).to_vec()?),
+				info.get::<cap::FromStatusLine>().map(|cap| cap.expand().to_vec()).transpose()?,
+			),
+
+			None => match info.get::<cap::ToTitle>() {
+				Some(to) => (
+					Some(to.expand().to_vec()?),
+					info.get::<cap::FromTitle>()
+						.map(|cap| cap.expand().to_vec())
+						.transpose()?
+						.or_else(|| Some(b"\x07".to_vec())),
+				),
+
+				None if info.get::<cap::XTermTitle>().is_some_and(|cap| cap.0) => {
+					(Some(b"\x1B]0;".to_vec()), Some(b"\x07".to_vec()))
+				}
+
+				None => (None, None),
+			},
+		};
+
+		Ok(Title {
+			to,
+			from: from.unwrap_or_default(),
+			escapes: status && info.get::<cap::StatusLineEscOk>().is_some_and(|cap| cap.0),
+			width: info.get::<cap::WidthStatusLine>().map(|cap| cap.0.max(0) as usize),
+		})
+	}
+
+	/// Whether the terminal supports setting the title.
+	pub fn is_supported(&self) -> bool {
+		self.to.is_some()
+	}
+
+	/// The sequence to set the title, if supported.
+	///
+	/// Control characters are removed unless `status_line_esc_ok` allows
+	/// escapes, and the text is truncated to `width_status_line`.
+	pub fn sequence(&self, title: &str) -> Option<Vec<u8>> {
+		let mut output = self.to.clone()?;
+		let text = title
+			.chars()
+			.filter(|&ch| !ch.is_control() || (self.escapes && ch == '\x1B'))
+			.take(self.width.unwrap_or(usize::MAX))
+			.collect::<String>();
+
+		output.extend(text.as_bytes());
+		output.extend(&self.from);
+
+		Some(output)
+	}
+
+	/// Set the title, returning whether the terminal supports it.
+	pub fn set_title<W: Write>(&self, mut output: W, title: &str) -> error::Result<bool> {
+		match self.sequence(title) {
+			Some(sequence) => {
+				output.write_all(&sequence)?;
+				Ok(true)
+			}
+
+			None => Ok(false),
+		}
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+
+	#[test]
+	fn status() {
+		let mut info = Database::new();
+		info.name("test")
+			.raw("hs", ())
+			.raw("eslok", ())
+			.raw("wsl", 8)
+			.raw("tsl", "\x1B_%p1%d;")
+			.raw("fsl", "\x1B\\")
+			.raw("XT", ());
+		let info = info.build().unwrap();
+
+		assert_eq!(
+			Some(b"\x1B_0;a\x1Bbcdefg\x1B\\".to_vec()),
+			Title::new(&info).unwrap().sequence("a\x1Bbcdefghij")
+		);
+
+		let info = Database::from_path("tests/xterm-256color").unwrap();
+		assert_eq!(
+			Some(b"\x1B]0;title\x07".to_vec()),
+			Title::new(&info).unwrap().sequence("title")
+		);
+
+		let mut info = Database::new();
+		info.name("test").raw("hs", ());
+		let info = info.build().unwrap();
+		assert!(!Title::new(&info).unwrap().is_supported());
+
+		let mut info = Database::new();
+		info.name("test").raw("TS", "\x1B]2;").raw("XT", ());
+		let info = info.build().unwrap();
+		assert_eq!(
+			Some(b"\x1B]2;title\x07".to_vec()),
+			Title::new(&info).unwrap().sequence("title\n")
+		);
+	}
+}